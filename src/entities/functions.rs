@@ -12,7 +12,7 @@
 //! The way these link together is:
 //! - `tables.keyspace_id` -> `keyspaces.keyspace_id` (keyspaces.keyspace_id must exist for tables.keyspace_id)
 //! - `tables.table_id` -> `tablets.table_id`, which contains the replicas as a nested array.
-//!     `tablets.table_id` might not exist for `tables.table_id`, because some tables do not have tablets, such as the postgres catalog entries.
+//!   `tablets.table_id` might not exist for `tables.table_id`, because some tables do not have tablets, such as the postgres catalog entries.
 //!
 //! # Special keyspaces:
 //!
@@ -424,8 +424,7 @@ impl AllEntities
                                 print!("{} ", entity.hostname_port.clone().unwrap());
                             }
                             let under_replication_warning = if under_replicated_tablets
-                                .iter()
-                                .any(|r| *r == tablet.tablet_id)
+                                .contains(&tablet.tablet_id)
                             {
                                 "[UNDER REPLICATED]".yellow()
                             }
@@ -581,8 +580,7 @@ impl AllEntities
                         print!("{} ", entity.hostname_port.clone().unwrap());
                     }
                     let under_replication_warning = if under_replicated_tablets
-                        .iter()
-                        .any(|r| *r == tablet.tablet_id)
+                        .contains(&tablet.tablet_id)
                     {
                         "[UNDER REPLICATED]".yellow()
                     }
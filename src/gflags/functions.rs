@@ -8,7 +8,8 @@ use anyhow::Result;
 use crate::Opts;
 use crate::utility;
 use crate::snapshot;
-use crate::gflags::{AllGFlags, GFlag};
+use colored::*;
+use crate::gflags::{AllGFlags, GFlag, GFlagsDiff, GFlagsDiffFields};
 
 impl AllGFlags {
     pub fn new() -> Self { Default::default() }
@@ -121,6 +122,131 @@ impl AllGFlags {
     }
 }
 
+impl GFlagsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<GFlagsDiff>
+    {
+        let mut gflagsdiff = GFlagsDiff::new();
+
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(begin_snapshot, "gflags")?;
+        gflagsdiff.first_snapshot(allgflags);
+
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(end_snapshot, "gflags")?;
+        gflagsdiff.second_snapshot(allgflags);
+
+        Ok(gflagsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allgflags: AllGFlags,
+    )
+    {
+        for gflag in allgflags.gflags
+        {
+            let hostname_port = gflag.hostname_port.expect("hostname:port should be set");
+            self.first_hostname_ports.insert(hostname_port.clone());
+            self.btreegflagsdiff
+                .entry((hostname_port.clone(), gflag.name.clone()))
+                .and_modify(|_| error!("Duplicate hostname:port + gflag name entry: {}, {}", hostname_port, gflag.name))
+                .or_insert( GFlagsDiffFields {
+                    first_value: Some(gflag.value),
+                    ..Default::default()
+                });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allgflags: AllGFlags,
+    )
+    {
+        for gflag in allgflags.gflags
+        {
+            let hostname_port = gflag.hostname_port.expect("hostname:port should be set");
+            self.second_hostname_ports.insert(hostname_port.clone());
+            self.btreegflagsdiff
+                .entry((hostname_port, gflag.name))
+                .or_default()
+                .second_value = Some(gflag.value);
+        }
+    }
+    /// Print the gflags that were added, removed or changed per host between the snapshots.
+    /// The gflags of a host that is present in one of the snapshots only are not shown,
+    /// because the host itself appeared or disappeared.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+    )
+    {
+        for ((hostname_port, name), row) in self.btreegflagsdiff
+            .iter()
+            .filter(|((hostname_port, name), _)| hostname_filter.is_match(hostname_port)
+                && stat_name_filter.is_match(name)
+                && self.first_hostname_ports.contains(hostname_port)
+                && self.second_hostname_ports.contains(hostname_port))
+        {
+            match (&row.first_value, &row.second_value)
+            {
+                (None, Some(second_value)) => {
+                    println!("{} {:20} GFlags: {:50} {}", "+".to_string().green(), hostname_port, name, second_value);
+                },
+                (Some(first_value), None) => {
+                    println!("{} {:20} GFlags: {:50} {}", "-".to_string().red(), hostname_port, name, first_value);
+                },
+                (Some(first_value), Some(second_value)) if first_value != second_value => {
+                    println!("{} {:20} GFlags: {:50} {}->{}", "=".to_string().yellow(), hostname_port, name, first_value.yellow(), second_value.yellow());
+                },
+                _ => {},
+            }
+        }
+    }
+    pub async fn adhoc_read_first_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    )
+    {
+        let allgflags = AllGFlags::read_gflags(hosts, ports, parallel).await;
+        self.first_snapshot(allgflags);
+    }
+    pub async fn adhoc_read_second_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    )
+    {
+        let allgflags = AllGFlags::read_gflags(hosts, ports, parallel).await;
+        self.second_snapshot(allgflags);
+    }
+}
+
+pub async fn gflags_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let gflagsdiff = GFlagsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    gflagsdiff.print(&hostname_filter, &stat_name_filter);
+
+    Ok(())
+}
+
 pub async fn print_gflags(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -149,6 +275,34 @@ pub async fn print_gflags(
 mod tests {
     use super::*;
 
+    #[test]
+    fn unit_gflags_diff() {
+        let gflag = |hostname_port: &str, name: &str, value: &str| GFlag {
+            hostname_port: Some(hostname_port.to_string()),
+            name: name.to_string(),
+            value: value.to_string(),
+            ..Default::default()
+        };
+        let mut gflagsdiff = GFlagsDiff::new();
+        gflagsdiff.first_snapshot(AllGFlags { gflags: vec![
+            gflag("yb-1.local:9000", "log_min_seconds_to_retain", "900"),
+            gflag("yb-1.local:9000", "removed_flag", "true"),
+            gflag("yb-1.local:9000", "webserver_interface", ""),
+        ]});
+        gflagsdiff.second_snapshot(AllGFlags { gflags: vec![
+            gflag("yb-1.local:9000", "log_min_seconds_to_retain", "3600"),
+            gflag("yb-1.local:9000", "webserver_interface", ""),
+            gflag("yb-1.local:9000", "vmodule", "tablet=1"),
+            gflag("yb-2.local:9000", "vmodule", ""),
+        ]});
+        let row = |hostname_port: &str, name: &str| &gflagsdiff.btreegflagsdiff[&(hostname_port.to_string(), name.to_string())];
+        assert_eq!(row("yb-1.local:9000", "log_min_seconds_to_retain"), &GFlagsDiffFields { first_value: Some("900".to_string()), second_value: Some("3600".to_string()) });
+        assert_eq!(row("yb-1.local:9000", "removed_flag"), &GFlagsDiffFields { first_value: Some("true".to_string()), second_value: None });
+        assert_eq!(row("yb-1.local:9000", "webserver_interface"), &GFlagsDiffFields { first_value: Some("".to_string()), second_value: Some("".to_string()) });
+        assert_eq!(row("yb-1.local:9000", "vmodule"), &GFlagsDiffFields { first_value: None, second_value: Some("tablet=1".to_string()) });
+        assert!(!gflagsdiff.first_hostname_ports.contains("yb-2.local:9000"));
+    }
+
     #[test]
     fn unit_parse_regular_gflags() {
         // These are the gflags of the tserver of version 2.15.2.1
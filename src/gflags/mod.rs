@@ -5,6 +5,7 @@
 //!     - Default (not changed)
 //!     - NodeInfo (set for local node identification)
 //!     - Custom (explicitly set to a value)
+//!
 //!   this property is not visible when the `/varz` endpoint is requested with `?raw` added.
 //!
 //! - the `/api/v1/varz` endpoint exists, which provides the gflags/vars data in JSON format.
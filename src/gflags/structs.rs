@@ -1,6 +1,7 @@
 //! The structs
 //!
 use chrono::{DateTime, Local};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// The root structure for deserializing the gflags
 /// This is taken from `/varz?raw`:
@@ -30,3 +31,23 @@ pub struct GFlag {
 pub struct AllGFlags {
     pub gflags: Vec<GFlag>,
 }
+// diff
+/// BTreeMap for storing a gflags diff struct per hostname_port, gflag name
+type BTreeGFlagsDiff = BTreeMap<(String, String), GFlagsDiffFields>;
+/// Wrapper struct for holding the btreemap, and the hosts found in the first and second snapshot.
+#[derive(Debug, Default)]
+pub struct GFlagsDiff {
+    pub btreegflagsdiff: BTreeGFlagsDiff,
+    pub first_hostname_ports: BTreeSet<String>,
+    pub second_hostname_ports: BTreeSet<String>,
+}
+/// The gflags diff struct.
+///
+/// The hostname:port and gflag name is the key of the btreemap,
+/// This struct holds the first and second value.
+/// A value is None if the gflag was not present in that snapshot, a gflag can be set to an empty value.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GFlagsDiffFields {
+    pub first_value: Option<String>,
+    pub second_value: Option<String>,
+}
@@ -13,4 +13,3 @@ mod structs;
 mod functions;
 
 pub use structs::*;
-pub use functions::*;
@@ -1,17 +1,17 @@
 //! The impls and functions.
 //!
-use std::{sync::mpsc::channel, time::{Instant, Duration}, collections::BTreeMap};
-use chrono::{DateTime, Utc, TimeZone};
+use std::{fs, io::{BufRead, BufReader, Write}, path::PathBuf, sync::{mpsc::channel, LazyLock}, time::{Instant, Duration}, collections::{BTreeMap, BTreeSet}};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
 use regex::{Regex,Captures};
 use log::*;
 use colored::*;
 use tokio::time;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use scraper::{Html, Selector};
 use crate::snapshot;
 use crate::Opts;
 use crate::utility;
-use crate::loglines::{AllLogLines, LogLine};
+use crate::loglines::{AllLogLines, LogLine, LogTemplate, LogFollower, LogStack};
 
 impl AllLogLines {
     pub fn new() -> Self { Default::default() }
@@ -114,30 +114,42 @@ impl AllLogLines {
             //format_stamp: "Formatted at 2022-02-13 16:26:17 on yb-1.local"
             let regular_log_line = Regex::new( r"([IWFE])(\d{2}\d{2} \d{2}:\d{2}:\d{2}\.\d{6})\s+(\d{1,6}) ([a-z_A-Z.:0-9]*)] (.*)\n" ).unwrap();
 
-            // Just take the year, it's not in the loglines, however, when the year switches this will lead to error results
-            let year= Utc::now().format("%Y").to_string();
-            let to_logline = |captures: Captures<'_>|
+            let stored_raw_loglines = &raw_loglines.text().collect::<String>();
+
+            // The year and timezone are not in the loglines, these are derived from the log file header.
+            let log_header = parse_log_header(stored_raw_loglines);
+            let utc_offset = log_header.map(|(_, utc_offset)| utc_offset).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+            let mut year_inference = YearInference::new(log_header.map(|(log_file_created_at, _)| log_file_created_at));
+            // A logline with a timestamp that can't be parsed, such as 0229 in an inferred year that is not a leap year,
+            // is skipped, together with the lines that belong to it.
+            let mut to_logline = |captures: Captures<'_>|
                 {
+                    let year = year_inference.year(&captures[2]);
                     let timestamp_string = format!("{}{}", year, &captures[2]);
-                    let timestamp = Utc
-                        .datetime_from_str(&timestamp_string, "%Y%m%d %H:%M:%S.%6f")
-                        .unwrap();
+                    let Some(timestamp) = NaiveDateTime::parse_from_str(&timestamp_string, "%Y%m%d %H:%M:%S.%6f")
+                        .ok()
+                        .and_then(|timestamp| utc_offset.from_local_datetime(&timestamp).single())
+                        .map(|timestamp| timestamp.with_timezone(&Utc))
+                    else {
+                        warn!("Unable to parse the timestamp of logline, skipping: {}", captures[0].trim_end());
+                        return None;
+                    };
 
-                    LogLine {
+                    Some(LogLine {
                         severity: captures[1].to_string(),
                         timestamp,
                         tid: captures[3].to_string(),
                         sourcefile_nr: captures[4].to_string(),
                         message: captures[5].to_string(),
+                        utc_offset: log_header.map(|(_, utc_offset)| utc_offset.local_minus_utc()),
                         ..Default::default()
-                    }
+                    })
                 };
             // Find first log line.  Any non-regular-log-line data at the beginning of
             // the logs is discarded.  `remaining` covers all the logs following the
             // first regular log line.
             let mut logline;
             let mut remaining;
-            let stored_raw_loglines = &raw_loglines.text().collect::<String>();
             match regular_log_line.captures(stored_raw_loglines)
             {
                 None => return loglines,
@@ -155,15 +167,21 @@ impl AllLogLines {
                 let all = captures.get(0).unwrap();
                 let from = all.start();
                 let offset = all.end();
-                logline.message += &remaining[..from];
-                loglines.push(logline);
+                if let Some(mut logline) = logline
+                {
+                    logline.message += &remaining[..from];
+                    loglines.push(logline);
+                }
                 logline = to_logline(captures);
                 remaining = &remaining[offset..]
             }
 
             // Append final logline and return
-            logline.message += remaining;
-            loglines.push(logline);
+            if let Some(mut logline) = logline
+            {
+                logline.message += remaining;
+                loglines.push(logline);
+            }
         }
 
         loglines
@@ -179,7 +197,7 @@ impl AllLogLines {
 
         // create a copy of the stored_loglines vector and sort it based on the timestamp.
         let mut sorted_loglines = self.loglines.clone();
-        sorted_loglines.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        sorted_loglines.sort_by_key(|a| a.timestamp);
         // use the sorted vector to loop over.
         for row in &sorted_loglines
         {
@@ -201,9 +219,383 @@ impl AllLogLines {
         }
         Ok(())
     }
+    /// Remove the loglines outside the window between from and to.
+    pub fn retain_window(
+        &mut self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    )
+    {
+        self.loglines.retain(|row| from.is_none_or(|from| row.timestamp >= from) && to.is_none_or(|to| row.timestamp <= to));
+    }
+    /// Print the loglines, the log summary, the loglines as JSON lines or the errors and their stacks, depending on the options.
+    fn print_or_summarise(
+        &self,
+        options: &Opts,
+    ) -> Result<()>
+    {
+        let hostname_filter = utility::set_regex(&options.hostname_match);
+        let stat_name_filter = utility::set_regex(&options.stat_name_match);
+        if options.log_json {
+            self.print_json(&hostname_filter, &stat_name_filter, &options.log_severity)
+        } else if options.log_errors {
+            self.print_errors(&hostname_filter, &stat_name_filter)
+        } else if options.log_summary {
+            self.print_summary(&hostname_filter, &stat_name_filter, &options.log_severity)
+        } else {
+            self.print(&hostname_filter, &stat_name_filter, &options.log_severity)
+        }
+    }
+    /// Return the loglines that match the filters, ordered by timestamp.
+    fn filtered(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> Vec<&LogLine>
+    {
+        let mut loglines: Vec<&LogLine> = self.loglines
+            .iter()
+            .filter(|row| hostname_filter.is_match(row.hostname_port.as_ref().expect("no hostname:port set"))
+                && log_severity.contains(&row.severity)
+                && ( stat_name_filter.is_match(&row.message) || stat_name_filter.is_match(&row.sourcefile_nr) ))
+            .collect();
+        loglines.sort_by_key(|row| row.timestamp);
+        loglines
+    }
+    /// Print the loglines as JSON lines: a JSON object per logline, for loading into other tools.
+    pub fn print_json(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> Result<()>
+    {
+        for row in self.filtered(hostname_filter, stat_name_filter, log_severity)
+        {
+            println!("{}", serde_json::to_string(row)?);
+        }
+        Ok(())
+    }
+    /// Group the fatal and error events by their stack trace.
+    ///
+    /// Returns the events without a stack trace, and the events with a stack trace grouped per stack.
+    fn group_stacks(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+    ) -> (Vec<&LogLine>, BTreeMap<Vec<String>, LogStack>)
+    {
+        let mut events = Vec::new();
+        let mut stacks: BTreeMap<Vec<String>, LogStack> = BTreeMap::new();
+        for row in self.filtered(hostname_filter, stat_name_filter, "FE")
+        {
+            let frames = parse_stack_frames(&row.message);
+            if frames.is_empty()
+            {
+                events.push(row);
+                continue;
+            }
+            stacks.entry(frames)
+                .and_modify(|logstack| {
+                    logstack.count += 1;
+                    logstack.servers.insert(row.hostname_port.clone().expect("no hostname:port set"));
+                    logstack.first_seen = logstack.first_seen.min(row.timestamp);
+                    logstack.last_seen = logstack.last_seen.max(row.timestamp);
+                })
+                .or_insert(LogStack {
+                    count: 1,
+                    servers: BTreeSet::from([row.hostname_port.clone().expect("no hostname:port set")]),
+                    first_seen: row.timestamp,
+                    last_seen: row.timestamp,
+                    message: row.message.trim().lines().next().unwrap_or_default().to_string(),
+                });
+        }
+        (events, stacks)
+    }
+    /// Print the fatal and error events with their full multi-line message.
+    /// Events with a stack trace are grouped by identical stacks across servers, and shown with the number of occurrences.
+    pub fn print_errors(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+    ) -> Result<()>
+    {
+        let (events, stacks) = self.group_stacks(hostname_filter, stat_name_filter);
+        for row in events
+        {
+            print!("{:20} {:33} ", row.hostname_port.clone().expect("no hostname:port set"), row.timestamp);
+            match row.severity.as_str()
+            {
+                "E" => print!("{} ", "E".red()),
+                "F" => print!("{} ", "F".purple()),
+                _   => print!("{} ", row.severity.underline()),
+            }
+            println!("{:20} {}", row.sourcefile_nr, row.message.trim_end());
+        }
+        let mut stacks: Vec<_> = stacks.into_iter().collect();
+        stacks.sort_by_key(|(_, logstack)| std::cmp::Reverse(logstack.count));
+        for (frames, logstack) in stacks
+        {
+            println!("{} {} occurrences on {} between {} and {}: {}",
+                     "stack".bold(),
+                     logstack.count,
+                     logstack.servers.iter().cloned().collect::<Vec<String>>().join(", "),
+                     logstack.first_seen,
+                     logstack.last_seen,
+                     logstack.message,
+            );
+            for frame in frames
+            {
+                println!("    {}", frame);
+            }
+        }
+        Ok(())
+    }
+    /// Count the loglines per template, severity, source file and host.
+    ///
+    /// The key is: template, severity, sourcefile_nr, hostname_port.
+    fn summarise(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> BTreeMap<(String, String, String, String), LogTemplate>
+    {
+        let mut templates: BTreeMap<(String, String, String, String), LogTemplate> = BTreeMap::new();
+        for row in self.loglines
+            .iter()
+            .filter(|row| hostname_filter.is_match(row.hostname_port.as_ref().expect("no hostname:port set"))
+                && log_severity.contains(&row.severity)
+                && ( stat_name_filter.is_match(&row.message) || stat_name_filter.is_match(&row.sourcefile_nr) ))
+        {
+            templates.entry((
+                template_message(&row.message),
+                row.severity.clone(),
+                row.sourcefile_nr.clone(),
+                row.hostname_port.clone().expect("no hostname:port set"),
+            ))
+                .and_modify(|logtemplate| {
+                    logtemplate.count += 1;
+                    logtemplate.first_seen = logtemplate.first_seen.min(row.timestamp);
+                    logtemplate.last_seen = logtemplate.last_seen.max(row.timestamp);
+                })
+                .or_insert(LogTemplate {
+                    count: 1,
+                    first_seen: row.timestamp,
+                    last_seen: row.timestamp,
+                });
+        }
+        templates
+    }
+    /// Print the number of occurrences per log message template, severity, source file and host, sorted by frequency.
+    pub fn print_summary(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> Result<()>
+    {
+        info!("print log summary");
+
+        let mut templates: Vec<_> = self.summarise(hostname_filter, stat_name_filter, log_severity).into_iter().collect();
+        templates.sort_by_key(|(_, logtemplate)| std::cmp::Reverse(logtemplate.count));
+        println!("{:>8} {:20} {:1} {:30} {:26} {:26} template",
+                 "count", "hostname_port", "S", "sourcefile_nr", "first_seen", "last_seen");
+        for ((template, severity, sourcefile_nr, hostname_port), logtemplate) in templates
+        {
+            print!("{:8} {:20} ", logtemplate.count, hostname_port);
+            match severity.as_str()
+            {
+                "I" => print!("{} ", "I".green()),
+                "W" => print!("{} ", "W".yellow()),
+                "E" => print!("{} ", "E".red()),
+                "F" => print!("{} ", "F".purple()),
+                _   => print!("{} ", severity.underline()),
+            }
+            println!("{:30} {:26} {:26} {}",
+                     sourcefile_nr,
+                     logtemplate.first_seen.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+                     logtemplate.last_seen.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+                     template,
+            );
+        }
+        Ok(())
+    }
 }
 
-pub async fn print_loglines(
+/// Parse the stack frames of a multi-line message, such as a fatal error or a long operation backtrace:
+/// ```text
+/// Check failed: ...
+/// *** Check failure stack trace: ***
+///     @     0x7fa344eb611f  (unknown)
+///     @     0x7fa345833b39  __lll_lock_wait
+/// ```
+/// Only the function name of a frame is returned, because the address differs between servers.
+pub fn parse_stack_frames(message: &str) -> Vec<String>
+{
+    static STACK_FRAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"@\s+0x[0-9a-fA-F]+\s+([^\n]+)").unwrap());
+    STACK_FRAME.captures_iter(message)
+        .map(|captures| captures[1].trim().to_string())
+        .collect()
+}
+
+/// Turn a log message into a template by masking the parts that vary between occurrences of the same message.
+///
+/// Only the first line of the message is used, multi-line messages (such as stack traces) are grouped by the first line.
+/// The following parts are masked, in this order:
+/// - UUIDs (with or without dashes): `<uuid>`
+/// - IP addresses and hostnames with a port: `<host>`
+/// - durations (a number with a time unit): `<duration>`
+/// - hexadecimal numbers: `<hex>`
+/// - numbers: `<n>`
+pub fn template_message(message: &str) -> String
+{
+    static UUID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[0-9a-fA-F]{8}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{12}\b").unwrap());
+    static HOST: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}(:\d+)?|[a-zA-Z][a-zA-Z0-9-]*(\.[a-zA-Z0-9-]+)*:\d+)\b").unwrap());
+    static SOURCE_FILE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.(cc|h|c|cpp|hpp):\d+$").unwrap());
+    static DURATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d+(\.\d+)?\s?(ns|us|ms|s|sec|secs|seconds|min|minutes|h)\b").unwrap());
+    static HEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b0x[0-9a-fA-F]+\b").unwrap());
+    static NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d+(\.\d+)?\b").unwrap());
+    let message = message.trim().lines().next().unwrap_or_default();
+    let message = UUID.replace_all(message, "<uuid>");
+    // source file locations, such as rpc.cc:221, look like a hostname with a port.
+    let message = HOST.replace_all(&message, |captures: &Captures| {
+        if SOURCE_FILE.is_match(&captures[0]) { captures[0].to_string() } else { "<host>".to_string() }
+    });
+    let message = DURATION.replace_all(&message, "<duration>");
+    let message = HEX.replace_all(&message, "<hex>");
+    NUMBER.replace_all(&message, "<n>").to_string()
+}
+
+impl LogFollower {
+    pub fn new() -> Self { Default::default() }
+    /// Return the loglines that are newer than the high-water mark of their server, and move the high-water mark.
+    ///
+    /// If a server has a high-water mark, and the oldest logline that is read is newer than it,
+    /// the loglines in between have scrolled out of /logs before they could be read.
+    /// For such a gap, a warning logline is added with sourcefile_nr 'yb_stats'.
+    fn new_loglines(
+        &mut self,
+        allloglines: AllLogLines,
+    ) -> Vec<LogLine>
+    {
+        let mut per_server: BTreeMap<String, Vec<LogLine>> = BTreeMap::new();
+        for logline in allloglines.loglines
+        {
+            per_server.entry(logline.hostname_port.clone().expect("no hostname:port set"))
+                .or_default()
+                .push(logline);
+        }
+
+        let mut new_loglines = Vec::new();
+        for (hostname_port, mut loglines) in per_server
+        {
+            loglines.sort_by_key(|logline| logline.timestamp);
+            let first_timestamp = loglines.first().map(|logline| logline.timestamp).unwrap_or_default();
+            match self.high_water_marks.get(&hostname_port)
+            {
+                Some(high_water_mark) if first_timestamp > high_water_mark.timestamp => {
+                    warn!("{}: loglines between {} and {} might be missing", hostname_port, high_water_mark.timestamp, first_timestamp);
+                    new_loglines.push(LogLine {
+                        hostname_port: Some(hostname_port.clone()),
+                        severity: "W".to_string(),
+                        timestamp: first_timestamp,
+                        sourcefile_nr: "yb_stats".to_string(),
+                        message: format!("gap: loglines between {} and {} might be missing, because these were no longer shown by /logs", high_water_mark.timestamp, first_timestamp),
+                        ..Default::default()
+                    });
+                },
+                _ => {},
+            }
+            let high_water_mark = self.high_water_marks.entry(hostname_port).or_default();
+            for logline in loglines
+            {
+                let key = (logline.tid.clone(), logline.sourcefile_nr.clone(), logline.message.clone());
+                if logline.timestamp < high_water_mark.timestamp
+                    || (logline.timestamp == high_water_mark.timestamp && high_water_mark.loglines.contains(&key))
+                {
+                    continue;
+                }
+                if logline.timestamp > high_water_mark.timestamp
+                {
+                    high_water_mark.timestamp = logline.timestamp;
+                    high_water_mark.loglines.clear();
+                }
+                high_water_mark.loglines.insert(key);
+                new_loglines.push(logline);
+            }
+        }
+        new_loglines
+    }
+}
+
+/// The path of the log store of a snapshot: the loglines written by `--follow-log`, in JSON lines format.
+fn log_store_path(
+    snapshot_number: &String,
+) -> Result<PathBuf>
+{
+    Ok(snapshot::snapshot_directory(snapshot_number)?.join("loglines.jsonl"))
+}
+
+/// The path of the end time of the log store of a snapshot: the time `--follow-log` last read the logs.
+fn log_store_end_time_path(
+    snapshot_number: &String,
+) -> Result<PathBuf>
+{
+    Ok(snapshot::snapshot_directory(snapshot_number)?.join("loglines_end_time"))
+}
+
+/// Read the time `--follow-log` last read the logs for the log store of a snapshot, if the snapshot has been followed.
+fn read_log_store_end_time(
+    snapshot_number: &String,
+) -> Result<Option<DateTime<Utc>>>
+{
+    let end_time_path = log_store_end_time_path(snapshot_number)?;
+    if !end_time_path.exists() {
+        return Ok(None);
+    }
+    let end_time = fs::read_to_string(&end_time_path)
+        .with_context(|| format!("Error reading log store end time: {}", end_time_path.display()))?;
+    let end_time = DateTime::parse_from_rfc3339(end_time.trim())
+        .with_context(|| format!("Invalid log store end time in: {}", end_time_path.display()))?;
+    Ok(Some(end_time.with_timezone(&Utc)))
+}
+
+/// Read the loglines of a snapshot.
+///
+/// This reads the loglines of the snapshot, and the loglines of the log store if these were added by `--follow-log`.
+pub fn read_snapshot_loglines(
+    snapshot_number: &String,
+) -> Result<Vec<LogLine>>
+{
+    let mut loglines: Vec<LogLine> = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
+    let log_store = log_store_path(snapshot_number)?;
+    if !log_store.exists() {
+        return Ok(loglines);
+    }
+    let file = fs::File::open(&log_store)
+        .with_context(|| format!("Error opening log store: {}", log_store.display()))?;
+    for line in BufReader::new(file).lines()
+    {
+        let line = line?;
+        if line.trim().is_empty() { continue };
+        loglines.push(serde_json::from_str(&line).with_context(|| format!("Json deserialization error in log store: {}", log_store.display()))?);
+    }
+    Ok(loglines)
+}
+
+/// Follow the logs of the servers, and add new loglines to the log store of an existing snapshot.
+///
+/// The high-water marks start at the loglines that are already in the snapshot,
+/// so the log store continues where the snapshot, or an earlier `--follow-log` of the snapshot, ended.
+/// Every 3 seconds /logs is read, and the loglines that are newer than the high-water mark of a server are appended to the log store.
+/// This way loglines that have scrolled out of the part of the log shown by /logs are kept.
+/// A gap, where loglines could not be read before they scrolled out, is written as a warning logline.
+/// After every read, the time of the read is written as the end time of the log store,
+/// so `--log-diff` includes the followed loglines up to that time.
+/// The loglines can be shown with `--print-log <snapshot number>`.
+pub async fn follow_loglines(
     hosts: Vec<&str>,
     ports: Vec<&str>,
     parallel: usize,
@@ -212,19 +604,179 @@ pub async fn print_loglines(
 {
     let hostname_filter = utility::set_regex(&options.hostname_match);
     let stat_name_filter = utility::set_regex(&options.stat_name_match);
-    match options.print_log.as_ref().unwrap()
+
+    let snapshot_number = options.follow_log.clone().unwrap();
+    // validate the snapshot exists in the snapshot index.
+    snapshot::Snapshot::read_snapshot_timestamp(&snapshot_number)?;
+    let mut logfollower = LogFollower::new();
+    logfollower.new_loglines(AllLogLines { loglines: read_snapshot_loglines(&snapshot_number)? });
+
+    let log_store = log_store_path(&snapshot_number)?;
+    let log_store_end_time = log_store_end_time_path(&snapshot_number)?;
+    let mut log_store_file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_store)
+        .with_context(|| format!("Error opening log store: {}", log_store.display()))?;
+
+    println!("Follow log ready, writing loglines to snapshot {}, showing severities: {}", snapshot_number, &options.log_severity);
+
+    let mut interval = time::interval(Duration::from_secs(3));
+    loop
+    {
+        interval.tick().await;
+        let read_time = Utc::now();
+        let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel).await;
+        let new_loglines = logfollower.new_loglines(allloglines);
+        for logline in &new_loglines
+        {
+            writeln!(log_store_file, "{}", serde_json::to_string(logline)?)?;
+        }
+        log_store_file.flush()?;
+        fs::write(&log_store_end_time, read_time.to_rfc3339())
+            .with_context(|| format!("Error writing log store end time: {}", log_store_end_time.display()))?;
+        AllLogLines { loglines: new_loglines }.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
+    }
+}
+
+/// Find the "Log file created at:" and "Current UTC time:" lines in the log file header.
+///
+/// Returns the local time the log file was created and the UTC offset of the server.
+/// Both lines are written at the same time, so the difference is the UTC offset,
+/// which is rounded to 15 minutes to remove the difference in seconds between writing the lines.
+fn parse_log_header(
+    raw_loglines: &str,
+) -> Option<(NaiveDateTime, FixedOffset)>
+{
+    let log_file_created_at = Regex::new(r"Log file created at: (\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2})").unwrap();
+    let current_utc_time = Regex::new(r"Current UTC time: (\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2})").unwrap();
+    let created_at = NaiveDateTime::parse_from_str(&log_file_created_at.captures(raw_loglines)?[1], "%Y/%m/%d %H:%M:%S").ok()?;
+    let utc_time = NaiveDateTime::parse_from_str(&current_utc_time.captures(raw_loglines)?[1], "%Y/%m/%d %H:%M:%S").ok()?;
+    let offset_seconds = (created_at - utc_time).num_seconds();
+    let offset_seconds = ((offset_seconds as f64 / 900_f64).round() * 900_f64) as i32;
+    Some((created_at, FixedOffset::east_opt(offset_seconds)?))
+}
+/// Derive the year of the loglines, which only contain month and day.
+///
+/// The year starts with the year the log file was created, and is increased
+/// when the month of the loglines switches from december to january.
+/// If the log file creation time is not known, the current year is used,
+/// or the previous year if the first logline has a month that is later than the current month.
+///
+/// Only a change from month 12 to month 1 between two consecutive loglines increases the year.
+/// A gap between two consecutive loglines that spans a year boundary, such as from november to february,
+/// or a gap of more than a year, is not detected, and results in a year that is too low.
+struct YearInference {
+    year: Option<i32>,
+    month: u32,
+}
+impl YearInference {
+    fn new(log_file_created_at: Option<NaiveDateTime>) -> Self
+    {
+        YearInference {
+            year: log_file_created_at.map(|created_at| created_at.year()),
+            month: log_file_created_at.map(|created_at| created_at.month()).unwrap_or_default(),
+        }
+    }
+    /// Return the year for the logline timestamp in the format `mmdd hh:mm:ss.uuuuuu`.
+    fn year(&mut self, timestamp: &str) -> i32
+    {
+        let month: u32 = timestamp.get(0..2).and_then(|month| month.parse().ok()).unwrap_or_default();
+        let year = match self.year {
+            Some(year) if self.month == 12 && month == 1 => year + 1,
+            Some(year) => year,
+            None => {
+                let now = Utc::now();
+                if month > now.month() { now.year() - 1 } else { now.year() }
+            },
+        };
+        self.year = Some(year);
+        self.month = month;
+        year
+    }
+}
+
+pub async fn print_loglines(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let mut allloglines = match options.print_log.as_ref().unwrap()
     {
         Some(snapshot_number) => {
             let mut allloglines = AllLogLines::new();
-            allloglines.loglines = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
-            allloglines.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
-        },
-        None => {
-            let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel).await;
-            allloglines.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
+            allloglines.loglines = read_snapshot_loglines(snapshot_number)?;
+            allloglines
         },
+        None => AllLogLines::read_loglines(&hosts, &ports, parallel).await,
+    };
+    allloglines.retain_window(parse_log_time(&options.log_from)?, parse_log_time(&options.log_to)?);
+    allloglines.print_or_summarise(options)
+}
+
+/// Parse the `--log-from` and `--log-to` times.
+///
+/// The time can be specified as RFC 3339 (`2023-02-03T11:13:22+01:00`),
+/// or as `YYYY-MM-DD HH:MM:SS` in the local timezone of the machine running yb_stats.
+fn parse_log_time(
+    log_time: &Option<String>,
+) -> Result<Option<DateTime<Utc>>>
+{
+    let Some(log_time) = log_time else { return Ok(None) };
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(log_time) {
+        return Ok(Some(timestamp.with_timezone(&Utc)));
+    }
+    let timestamp = NaiveDateTime::parse_from_str(log_time, "%Y-%m-%d %H:%M:%S")
+        .with_context(|| format!("Invalid log time: {}, use YYYY-MM-DD HH:MM:SS or RFC 3339", log_time))?;
+    Local.from_local_datetime(&timestamp)
+        .single()
+        .map(|timestamp| Some(timestamp.with_timezone(&Utc)))
+        .with_context(|| format!("Ambiguous local log time: {}", log_time))
+}
+
+/// Print the loglines between the timestamps of the begin and the end snapshot.
+///
+/// The loglines of both snapshots are combined, because /logs only shows the last part of the log,
+/// and therefore the end snapshot might not contain all the loglines since the begin snapshot.
+/// If the end snapshot has been followed with `--follow-log`, the window ends at the end time of its log store instead,
+/// so the followed loglines are included.
+/// The `--log-from` and `--log-to` times can be used to narrow down the window further.
+pub fn print_loglines_between_snapshots(
+    begin_snapshot: &String,
+    end_snapshot: &String,
+    options: &Opts,
+) -> Result<()>
+{
+    let mut allloglines = AllLogLines::new();
+    allloglines.loglines = read_snapshot_loglines(begin_snapshot)?;
+    allloglines.loglines.extend(read_snapshot_loglines(end_snapshot)?);
+    allloglines.loglines.sort_by(|a, b| (&a.hostname_port, a.timestamp, &a.tid, &a.message).cmp(&(&b.hostname_port, b.timestamp, &b.tid, &b.message)));
+    allloglines.loglines.dedup_by(|a, b| a.hostname_port == b.hostname_port && a.timestamp == b.timestamp && a.tid == b.tid && a.message == b.message);
+
+    let begin_time = snapshot::Snapshot::read_snapshot_timestamp(begin_snapshot)?.with_timezone(&Utc);
+    let end_time = snapshot::Snapshot::read_snapshot_timestamp(end_snapshot)?.with_timezone(&Utc);
+    let end_time = read_log_store_end_time(end_snapshot)?.map_or(end_time, |log_store_end_time| log_store_end_time.max(end_time));
+    let log_from = parse_log_time(&options.log_from)?.map_or(begin_time, |log_from| log_from.max(begin_time));
+    let log_to = parse_log_time(&options.log_to)?.map_or(end_time, |log_to| log_to.min(end_time));
+    allloglines.retain_window(Some(log_from), Some(log_to));
+
+    allloglines.print_or_summarise(options)
+}
+
+pub async fn log_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
     }
-    Ok(())
+    if options.snapshot_list { return Ok(()) };
+
+    let (begin_snapshot, end_snapshot, _) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    print_loglines_between_snapshots(&begin_snapshot, &end_snapshot, options)
 }
 
 pub async fn tail_loglines(
@@ -264,7 +816,7 @@ pub async fn tail_loglines(
         // add all loglines that are not found in the second loglines snapshot to display loglines
         for (key, value) in &second_loglines_btreemap
         {
-            if first_loglines_btreemap.get(key).is_none()
+            if !first_loglines_btreemap.contains_key(key)
             {
                 display_loglines_btreemap.insert( key.clone(), value.clone());
             }
@@ -315,6 +867,147 @@ I1218 12:12:34.464808  7650 client-internal.cc:2302] New master addresses: [yb-1
         println!("{:#?}", result);
     }
 
+    #[test]
+    fn unit_parse_logline_header_year_and_offset() {
+        // The server is in UTC+1, and the log rolls over into the next year.
+        let logline = r#"
+        <div class='yb-main container-fluid'><pre>
+Log file created at: 2022/12/31 23:59:58
+Current UTC time: 2022/12/31 22:59:58
+Running on machine: yb-1.local
+Log line format: [IWEF]mmdd hh:mm:ss.uuuuuu threadid file:line] msg
+I1231 23:59:59.000000  7164 server_main_util.cc:72] NumCPUs determined to be: 4
+I0101 00:00:01.000000  7164 server_main_util.cc:72] Happy new year
+        </pre></div>
+        "#.to_string();
+        let result = AllLogLines::parse_loglines(logline);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].timestamp, Utc.with_ymd_and_hms(2022, 12, 31, 22, 59, 59).unwrap());
+        assert_eq!(result[1].timestamp, Utc.with_ymd_and_hms(2022, 12, 31, 23, 0, 1).unwrap());
+        assert_eq!(result[1].utc_offset, Some(3600));
+    }
+
+    #[test]
+    fn unit_parse_logline_invalid_timestamp() {
+        // 2023 is not a leap year, so 0229 is not a valid date: the logline and its continuation line are skipped.
+        let logline = r#"
+        <div class='yb-main container-fluid'><pre>
+Log file created at: 2023/02/28 10:00:00
+Current UTC time: 2023/02/28 10:00:00
+Running on machine: yb-1.local
+Log line format: [IWEF]mmdd hh:mm:ss.uuuuuu threadid file:line] msg
+I0228 10:00:01.000000  7164 server_main_util.cc:72] first
+I0229 10:00:02.000000  7164 server_main_util.cc:72] invalid
+continuation of invalid
+I0301 10:00:03.000000  7164 server_main_util.cc:72] last
+        </pre></div>
+        "#.to_string();
+        let result = AllLogLines::parse_loglines(logline);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].message.trim(), "first");
+        assert_eq!(result[1].message.trim(), "last");
+        assert_eq!(result[1].timestamp, Utc.with_ymd_and_hms(2023, 3, 1, 10, 0, 3).unwrap());
+    }
+
+    #[test]
+    fn unit_template_message() {
+        assert_eq!(template_message("T 5a1c4d3a37c8447fb1c4f1c0c2c7a8a0 P 05b8d176-20eb-4cd7-9edd-addb2fbcbb42: Leader not ready to serve requests"),
+                   "T <uuid> P <uuid>: Leader not ready to serve requests");
+        assert_eq!(template_message("Timed out (yb/rpc/rpc.cc:221): Could not locate the leader master at yb-1.local:7100, 192.168.66.80:7100 after 34.910s"),
+                   "Timed out (yb/rpc/rpc.cc:<n>): Could not locate the leader master at <host>, <host> after <duration>");
+        assert_eq!(template_message("Time spent Read: real 1.234s user 0.001s at frame 0x7f12ab"),
+                   "Time spent Read: real <duration> user <duration> at frame <hex>");
+        // only the first line is used.
+        assert_eq!(template_message("Flushed 123 entries\n@ 0x7f12ab second line"), "Flushed <n> entries");
+    }
+
+    #[test]
+    fn unit_log_summary() {
+        let allloglines = AllLogLines {
+            loglines: (0..3).map(|tablet| LogLine {
+                hostname_port: Some("yb-1.local:9000".to_string()),
+                severity: "W".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, tablet).unwrap(),
+                sourcefile_nr: "tablet_service.cc:123".to_string(),
+                message: format!("T {:032} Leader not ready", tablet),
+                ..Default::default()
+            }).collect(),
+        };
+        let result = allloglines.summarise(&utility::set_regex(&None), &utility::set_regex(&None), "WEF");
+        assert_eq!(result.len(), 1);
+        let ((template, _, _, _), logtemplate) = result.iter().next().unwrap();
+        assert_eq!(template, "T <uuid> Leader not ready");
+        assert_eq!(logtemplate.count, 3);
+        assert_eq!(logtemplate.first_seen, Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, 0).unwrap());
+        assert_eq!(logtemplate.last_seen, Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, 2).unwrap());
+    }
+
+    #[test]
+    fn unit_loglines_retain_window() {
+        let mut allloglines = AllLogLines {
+            loglines: (0..5).map(|second| LogLine {
+                timestamp: Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, second).unwrap(),
+                ..Default::default()
+            }).collect(),
+        };
+        allloglines.retain_window(parse_log_time(&Some("2023-02-03T11:00:01Z".to_string())).unwrap(), parse_log_time(&Some("2023-02-03T12:00:03+01:00".to_string())).unwrap());
+        assert_eq!(allloglines.loglines.len(), 3);
+        allloglines.retain_window(None, Some(Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, 1).unwrap()));
+        assert_eq!(allloglines.loglines.len(), 1);
+        assert!(parse_log_time(&Some("yesterday".to_string())).is_err());
+        assert!(parse_log_time(&Some("2023-02-03 11:00:01".to_string())).unwrap().is_some());
+    }
+
+    #[test]
+    fn unit_log_follower_new_loglines_and_gaps() {
+        let logline = |second: u32, message: &str| LogLine {
+            hostname_port: Some("yb-1.local:9000".to_string()),
+            severity: "I".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2023, 2, 3, 11, 0, second).unwrap(),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let mut logfollower = LogFollower::new();
+        let result = logfollower.new_loglines(AllLogLines { loglines: vec![logline(1, "a"), logline(2, "b")] });
+        assert_eq!(result.len(), 2);
+        // b was already stored, c has the same timestamp but is new.
+        let result = logfollower.new_loglines(AllLogLines { loglines: vec![logline(1, "a"), logline(2, "b"), logline(2, "c"), logline(3, "d")] });
+        assert_eq!(result.iter().map(|r| r.message.as_str()).collect::<Vec<&str>>(), vec!["c", "d"]);
+        // the oldest logline is newer than the high-water mark: a gap.
+        let result = logfollower.new_loglines(AllLogLines { loglines: vec![logline(10, "e")] });
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].sourcefile_nr, "yb_stats");
+        assert!(result[0].message.starts_with("gap:"));
+        assert_eq!(result[1].message, "e");
+    }
+
+    #[test]
+    fn unit_log_group_stacks() {
+        let logline = |hostname_port: &str, severity: &str, message: &str| LogLine {
+            hostname_port: Some(hostname_port.to_string()),
+            severity: severity.to_string(),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let allloglines = AllLogLines {
+            loglines: vec![
+                logline("yb-1.local:9000", "F", "Check failed: x\n    @     0x7fa344eb611f  google::LogMessage::Fail()\n    @     0x7fa353ff6e40  yb::tablet::Tablet::WriteToRocksDB()\n"),
+                logline("yb-2.local:9000", "F", "Check failed: x\n    @     0x7fb000000000  google::LogMessage::Fail()\n    @     0x7fb000000001  yb::tablet::Tablet::WriteToRocksDB()\n"),
+                logline("yb-2.local:9000", "E", "Failed to initialize client"),
+                logline("yb-2.local:9000", "W", "Stack in a warning\n    @     0x7fb000000000  google::LogMessage::Fail()\n"),
+            ],
+        };
+        let (events, stacks) = allloglines.group_stacks(&utility::set_regex(&None), &utility::set_regex(&None));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "Failed to initialize client");
+        assert_eq!(stacks.len(), 1);
+        let (frames, logstack) = stacks.iter().next().unwrap();
+        assert_eq!(frames, &vec!["google::LogMessage::Fail()".to_string(), "yb::tablet::Tablet::WriteToRocksDB()".to_string()]);
+        assert_eq!(logstack.count, 2);
+        assert_eq!(logstack.servers.len(), 2);
+        assert_eq!(logstack.message, "Check failed: x");
+    }
+
     #[test]
     fn unit_parse_regular_logline() {
         // This is a regular log line.
@@ -1,15 +1,18 @@
 //! The structs
 //!
 use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// The root struct for deserializing the glog lines.
 ///
 /// Please mind the glog format has some severe flaws for parsing.
-/// - The timestamp has no year field, so we have to guess the year,
-///   which is problematic when the timestamps include december and january.
-/// - The timestamp has no timezone indicator, so we have to guess that too.
-///   This is problematic if cluster nodes are in different timezones, and the servers set with different ones.
-///   The parser has to guess the timezone, so it picks the parser machine timezone (Local).
+/// - The timestamp has no year field, so we have to derive the year.
+///   The year is taken from the "Log file created at:" header line, and increased when the loglines switch from december to january.
+///   If the header is not shown, because the log is bigger than the size shown by the /logs page, the current year is used,
+///   or the previous year if the month of the logline is later than the current month.
+/// - The timestamp has no timezone indicator, and is in the local time of the server.
+///   The UTC offset is derived from the "Log file created at:" (local time) and "Current UTC time:" header lines,
+///   and is used to convert the timestamp to UTC. If the header is not shown, the timestamp is assumed to be UTC.
 ///
 /// There is no `pub timestamp: Option<DateTime<Local>>` field, because the fetch timestamp would have no function.
 /// The only timestamp that matters is the logging timestamp, which is in the logging itself.
@@ -23,9 +26,50 @@ pub struct LogLine {
     pub tid: String,
     pub sourcefile_nr: String,
     pub message: String,
+    /// The UTC offset of the server in seconds, derived from the log file header.
+    /// None if the header was not found.
+    pub utc_offset: Option<i32>,
 }
 
 #[derive(Debug, Default)]
 pub struct AllLogLines {
     pub loglines: Vec<LogLine>,
-}
\ No newline at end of file
+}
+/// The struct that holds the occurrences of a log message template for the log summary.
+///
+/// The template is the first line of the message with the variable parts masked,
+/// see [crate::loglines::template_message].
+#[derive(Debug, Default)]
+pub struct LogTemplate {
+    pub count: usize,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+/// The high-water mark of a server for following the log.
+///
+/// This is the timestamp of the latest logline that has been stored,
+/// and the loglines with that timestamp, so loglines with the same timestamp are not stored twice.
+#[derive(Debug, Default)]
+pub struct HighWaterMark {
+    pub timestamp: DateTime<Utc>,
+    /// (tid, sourcefile_nr, message)
+    pub loglines: BTreeSet<(String, String, String)>,
+}
+/// The struct that keeps the high-water mark per server (hostname_port) for following the log.
+#[derive(Debug, Default)]
+pub struct LogFollower {
+    pub high_water_marks: BTreeMap<String, HighWaterMark>,
+}
+/// The struct that holds the fatal and error events that have the same stack trace.
+///
+/// The stack is identified by the function names of the frames, not by the addresses,
+/// because the addresses are different between servers and server restarts.
+#[derive(Debug, Default)]
+pub struct LogStack {
+    pub count: usize,
+    pub servers: BTreeSet<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// The first line of the message of the first event with this stack.
+    pub message: String,
+}
@@ -72,6 +72,12 @@ pub struct Opts {
     /// Snapshot capture parallelism (default 1)
     #[arg(short = 'p', long, value_name = "nr")]
     parallel: Option<String>,
+    /// The metrics endpoint of the YugabyteDB servers: json (/metrics), prometheus (/prometheus-metrics), or auto (prometheus if json fails)
+    #[arg(long, value_enum, value_name = "source", default_value = "json")]
+    metrics_source: metrics::MetricsSource,
+    /// Additional prometheus endpoints to snapshot, comma separated: name=host:port/path
+    #[arg(long, value_name = "name=host:port/path")]
+    prometheus_targets: Option<String>,
     /// Output filter for statistic names as regex
     #[arg(short, long, value_name = "regex")]
     stat_name_match: Option<String>,
@@ -105,6 +111,12 @@ pub struct Opts {
     /// Create a metric diff report using a begin and end snapshot number.
     #[arg(long)]
     metrics_diff: bool,
+    /// Create a cdc and cdcsdk stream diff report using a begin and end snapshot number.
+    #[arg(long)]
+    cdc_diff: bool,
+    /// Create a rocksdb efficiency report (cache hit, bloom filter, amplification, stall) using a begin and end snapshot number.
+    #[arg(long)]
+    rocksdb_diff: bool,
     /// Create an entity diff report using a begin and end snapshot number.
     #[arg(long)]
     entity_diff: bool,
@@ -123,15 +135,57 @@ pub struct Opts {
     /// Create a (YSQL) statements diff report using a begin and end snapshot number.
     #[arg(long)]
     statements_diff: bool,
+    /// Create a cluster-wide statements diff report, grouped by query_id, using a begin and end snapshot number.
+    #[arg(long)]
+    statements_cluster_diff: bool,
+    /// Create a gflags diff report using a begin and end snapshot number.
+    #[arg(long)]
+    gflags_diff: bool,
     /// Create a versions diff report using a begin and end snapshot number.
     #[arg(long)]
     versions_diff: bool,
     /// Create an adhoc diff report only for metrics
     #[arg(long)]
     adhoc_metrics_diff: bool,
+    /// Create a memtrackers diff report using a begin and end snapshot number.
+    #[arg(long)]
+    memtrackers_diff: bool,
+    /// Create a tcmalloc statistics (memz) diff report using a begin and end snapshot number.
+    #[arg(long)]
+    mems_diff: bool,
+    /// Create a heap growth profile (pprof) diff report using a begin and end snapshot number.
+    #[arg(long)]
+    pprof_diff: bool,
+    /// Create a threads CPU diff report using a begin and end snapshot number.
+    #[arg(long)]
+    threads_diff: bool,
+    /// Create an adhoc threads CPU diff report.
+    #[arg(long)]
+    adhoc_threads_diff: bool,
     /// Create an adhoc diff report only for node_exporter
     #[arg(long)]
     adhoc_node_exporter_diff: bool,
+    /// Create an iostat-like disk report using a begin and end snapshot number.
+    #[arg(long)]
+    iostat_diff: bool,
+    /// Create an ad-hoc iostat-like disk report in memory.
+    #[arg(long)]
+    adhoc_iostat_diff: bool,
+    /// Print an iostat-like disk report of the current node_exporter data every given number of seconds.
+    #[arg(long, value_name = "seconds")]
+    iostat_live: Option<u64>,
+    /// Create a CPU, memory and network summary per host using a begin and end snapshot number.
+    #[arg(long)]
+    node_summary_diff: bool,
+    /// Create an ad-hoc CPU, memory and network summary per host in memory.
+    #[arg(long)]
+    adhoc_node_summary_diff: bool,
+    /// Print a CPU, memory and network summary per host of the current node_exporter data every given number of seconds.
+    #[arg(long, value_name = "seconds")]
+    node_summary_live: Option<u64>,
+    /// Create a diff report of the additional prometheus targets using a begin and end snapshot number.
+    #[arg(long)]
+    prometheus_diff: bool,
     /// Create an adhoc diff report excluding metrics
     #[arg(long)]
     adhoc_nonmetrics_diff: bool,
@@ -144,18 +198,51 @@ pub struct Opts {
     /// Output setting to specify the end snapshot number for diff report.
     #[arg(short = 'e', long, value_name = "snapshot number")]
     end: Option<i32>,
+    /// Print metrics for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_metrics: Option<Option<String>>,
+    /// Print statements for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_statements: Option<Option<String>>,
     /// Print memtrackers data for the given snapshot number
     #[arg(long, value_name = "snapshot number")]
     print_memtrackers: Option<Option<String>>,
+    /// Collapse the memtrackers below this depth with --print-memtrackers.
+    #[arg(long, value_name = "depth")]
+    memtrackers_depth: Option<usize>,
+    /// Print the top N memtrackers by consumption compared with the other hosts with --print-memtrackers.
+    #[arg(long, value_name = "nr")]
+    memtrackers_top: Option<usize>,
     /// tail log data
     #[arg(long)]
     tail_log: bool,
+    /// Follow log data, and add new loglines to the log store of the given snapshot (use --print-log to replay)
+    #[arg(long, value_name = "snapshot number")]
+    follow_log: Option<String>,
     /// Print log data for the given snapshot number
     #[arg(long, value_name = "snapshot number")]
     print_log: Option<Option<String>>,
     /// Output log data severity to include: optional: I (use with --print_log)
     #[arg(long, default_value = "WEF")]
     log_severity: String,
+    /// Print a summary of the log data, counting the messages per template, severity, source file and host (use with --print-log)
+    #[arg(long)]
+    log_summary: bool,
+    /// Print the log data as JSON lines (use with --print-log or --log-diff)
+    #[arg(long)]
+    log_json: bool,
+    /// Print the fatal and error log events with their full message, and group identical stack traces (use with --print-log or --log-diff)
+    #[arg(long)]
+    log_errors: bool,
+    /// Only show log data from this time: YYYY-MM-DD HH:MM:SS (local time) or RFC 3339 (use with --print-log or --log-diff)
+    #[arg(long, value_name = "time")]
+    log_from: Option<String>,
+    /// Only show log data up to this time: YYYY-MM-DD HH:MM:SS (local time) or RFC 3339 (use with --print-log or --log-diff)
+    #[arg(long, value_name = "time")]
+    log_to: Option<String>,
+    /// Print the log data between the begin and end snapshot times, also added to --snapshot-diff when set
+    #[arg(long)]
+    log_diff: bool,
     /// Print entity data for snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_entities: Option<Option<String>>,
@@ -180,9 +267,33 @@ pub struct Opts {
     /// print master leader tablet server latencies
     #[arg(long, value_name = "snapshot_number")]
     print_latencies: Option<Option<String>>,
+    /// Print tcmalloc statistics (memz) for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_mems: Option<Option<String>>,
+    /// Print the top allocation stacks of the heap growth profiles (pprof) for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_pprof: Option<Option<String>>,
+    /// Take a CPU profile of the given number of seconds, and save it in the snapshot taken with --snapshot.
+    #[arg(long, value_name = "seconds", requires = "snapshot")]
+    cpu_profile: Option<u64>,
+    /// Print the top functions of the CPU profiles for the given snapshot number.
+    #[arg(long, value_name = "snapshot number")]
+    print_cpu_profile: Option<String>,
+    /// Print the CPU profile stacks in folded stack format with --print-cpu-profile.
+    #[arg(long)]
+    cpu_profile_folded: bool,
+    /// The number of stacks or functions to print for the pprof reports.
+    #[arg(long, value_name = "nr", default_value = "20")]
+    pprof_top: usize,
     /// Print threads data for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_threads: Option<Option<String>>,
+    /// Print the threads grouped by identical stack with --print-threads.
+    #[arg(long)]
+    threads_stacks: bool,
+    /// Print the thread stacks in folded stack format for the given snapshot numbers, or get current.
+    #[arg(long, value_name = "snapshot number", num_args = 0..)]
+    threads_folded: Option<Vec<String>>,
     /// Print gflags for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_gflags: Option<Option<String>>,
@@ -216,9 +327,18 @@ pub struct Opts {
     /// Snapshot add very detailed data to snapshot
     #[arg(long)]
     extra_data: bool,
+    /// Replace the literals in SQL texts of statements and rpcs with placeholders, in snapshots and output.
+    #[arg(long)]
+    redact_sql: bool,
     /// Output setting for the length of the SQL text to display
     #[arg(long, value_name = "nr", default_value = "80")]
     sql_length: usize,
+    /// Output setting for the order of the statements in the cluster-wide report
+    #[arg(long, value_enum, value_name = "order", default_value = "total_time")]
+    statements_sort: statements::ClusterStatementsSort,
+    /// Output setting for the order of the statements in the per server report of --print-statements
+    #[arg(long, value_enum, value_name = "order", default_value = "total_time")]
+    print_statements_sort: statements::StatementsSort,
     /// Get the hostname for the tablet leader of a colocated YSQL database.
     #[arg(long, hide = true, value_name = "ysql colocated database name")]
     get_coloc_leader_host: Option<String>,
@@ -236,22 +356,33 @@ async fn main() -> Result<()>
     let hosts = utility::set_hosts(&options.hosts, &mut changed_options);
     let ports = utility::set_ports(&options.ports, &mut changed_options);
     let parallel = utility::set_parallel(&options.parallel, &mut changed_options);
+    let prometheus_targets = utility::set_prometheus_targets(&options.prometheus_targets, &mut changed_options);
 
     match &options {
-        Opts { snapshot, ..                 } if *snapshot                       => snapshot::perform_snapshot(hosts, ports, parallel, &options).await?,
+        Opts { snapshot, ..                 } if *snapshot                       => snapshot::perform_snapshot(hosts, ports, node_exporter::PrometheusTarget::parse_targets(&prometheus_targets)?, parallel, &options).await?,
         Opts { snapshot_diff, ..            } if *snapshot_diff                  => snapshot::snapshot_diff(&options).await?,
         Opts { snapshot_nonmetrics_diff, .. } if *snapshot_nonmetrics_diff       => snapshot::snapshot_nonmetrics_diff(&options).await?,
         Opts { snapshot_list, ..            } if *snapshot_list                  => snapshot::snapshot_diff(&options).await?,
         Opts { metrics_diff, ..              } if *metrics_diff                    => metrics::metrics_diff(&options).await?,
+        Opts { cdc_diff, ..                 } if *cdc_diff                       => metrics::cdc_diff(&options).await?,
+        Opts { rocksdb_diff, ..             } if *rocksdb_diff                   => metrics::rocksdb_diff(&options).await?,
         Opts { entity_diff, ..              } if *entity_diff                    => entities::entity_diff(&options).await?,
         Opts { masters_diff, ..             } if *masters_diff                   => masters::masters_diff(&options).await?,
         Opts { tablet_servers_diff, ..             } if *tablet_servers_diff                   => tablet_servers::tablet_servers_diff(&options).await?,
         Opts { vars_diff, ..             } if *vars_diff                   => vars::vars_diff(&options).await?,
         Opts { node_exporter_diff, ..             } if *node_exporter_diff                   => node_exporter::node_exporter_diff(&options).await?,
         Opts { statements_diff, ..             } if *statements_diff                   => statements::statements_diff(&options).await?,
+        Opts { statements_cluster_diff, .. } if *statements_cluster_diff        => statements::statements_cluster_diff(&options).await?,
+        Opts { gflags_diff, ..              } if *gflags_diff                    => gflags::gflags_diff(&options).await?,
         Opts { versions_diff, ..            } if *versions_diff                  => versions::versions_diff(&options).await?,
+        Opts { print_metrics, ..            } if print_metrics.is_some()         => metrics::print_metrics(hosts, ports, parallel, &options).await?,
+        Opts { print_statements, ..         } if print_statements.is_some()      => statements::print_statements(hosts, ports, parallel, &options).await?,
         Opts { print_memtrackers, ..        } if print_memtrackers.is_some()     => memtrackers::print_memtrackers(hosts, ports, parallel, &options).await?,
         Opts { print_version, ..            } if print_version.is_some()         => versions::print_version(hosts, ports, parallel, &options).await?,
+        Opts { threads_folded, ..           } if threads_folded.is_some()        => threads::print_threads_folded(hosts, ports, parallel, &options).await?,
+        Opts { print_mems, ..               } if print_mems.is_some()            => mems::print_mems(hosts, ports, parallel, &options).await?,
+        Opts { print_cpu_profile, ..        } if print_cpu_profile.is_some()     => pprof::print_cpu_profile(&options).await?,
+        Opts { print_pprof, ..              } if print_pprof.is_some()           => pprof::print_pprof(hosts, ports, parallel, &options).await?,
         Opts { print_threads, ..            } if print_threads.is_some()         => threads::print_threads(hosts, ports, parallel, &options).await?,
         Opts { print_entities, ..           } if print_entities.is_some()        => entities::print_entities(hosts, ports, parallel, &options).await?,
         Opts { print_masters, ..            } if print_masters.is_some()         => masters::print_masters(hosts, ports, parallel, &options).await?,
@@ -262,8 +393,22 @@ async fn main() -> Result<()>
         Opts { print_rpcs, ..               } if print_rpcs.is_some()            => rpcs::print_rpcs(hosts, ports, parallel, &options).await?,
         Opts { print_log, ..                } if print_log.is_some()             => loglines::print_loglines(hosts, ports, parallel, &options).await?,
         Opts { tail_log, ..                 } if *tail_log                       => loglines::tail_loglines(hosts, ports, parallel, &options).await?,
+        Opts { log_diff, ..                 } if *log_diff                       => loglines::log_diff(&options).await?,
+        Opts { follow_log, ..               } if follow_log.is_some()            => loglines::follow_loglines(hosts, ports, parallel, &options).await?,
         Opts { adhoc_metrics_diff, ..       } if *adhoc_metrics_diff             => snapshot::adhoc_metrics_diff(hosts, ports, parallel, &options).await?,
         Opts { adhoc_node_exporter_diff, ..       } if *adhoc_node_exporter_diff             => snapshot::adhoc_node_exporter_diff(hosts, ports, parallel, &options).await?,
+        Opts { memtrackers_diff, ..         } if *memtrackers_diff               => memtrackers::memtrackers_diff(&options).await?,
+        Opts { mems_diff, ..                } if *mems_diff                      => mems::mems_diff(&options).await?,
+        Opts { pprof_diff, ..               } if *pprof_diff                     => pprof::pprof_diff(&options).await?,
+        Opts { threads_diff, ..             } if *threads_diff                   => threads::threads_diff(&options).await?,
+        Opts { adhoc_threads_diff, ..       } if *adhoc_threads_diff             => snapshot::adhoc_threads_diff(hosts, ports, parallel, &options).await?,
+        Opts { iostat_diff, ..              } if *iostat_diff                    => node_exporter::iostat_diff(&options).await?,
+        Opts { adhoc_iostat_diff, ..        } if *adhoc_iostat_diff              => snapshot::adhoc_node_exporter_diff(hosts, ports, parallel, &options).await?,
+        Opts { iostat_live, ..              } if iostat_live.is_some()           => node_exporter::iostat_live(hosts, ports, parallel, &options).await?,
+        Opts { node_summary_diff, ..        } if *node_summary_diff              => node_exporter::node_summary_diff(&options).await?,
+        Opts { adhoc_node_summary_diff, ..  } if *adhoc_node_summary_diff        => snapshot::adhoc_node_exporter_diff(hosts, ports, parallel, &options).await?,
+        Opts { node_summary_live, ..        } if node_summary_live.is_some()     => node_exporter::node_summary_live(hosts, ports, parallel, &options).await?,
+        Opts { prometheus_diff, ..          } if *prometheus_diff                => node_exporter::prometheus_diff(&options).await?,
         Opts { adhoc_nonmetrics_diff, ..    } if *adhoc_nonmetrics_diff          => snapshot::adhoc_nonmetrics_diff(hosts, ports, parallel, &options).await?,
         Opts { print_gflags, ..             } if print_gflags.is_some()          => gflags::print_gflags(hosts, ports, parallel, &options).await?,
         Opts { print_cluster_config, ..     } if print_cluster_config.is_some()  => cluster_config::print_cluster_config(hosts, ports, parallel, &options).await?,
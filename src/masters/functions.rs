@@ -192,17 +192,14 @@ impl Masters {
             };
             println!(")");
             // fifth row: only if errors are reported
-            if row.error.is_some() {
+            if let Some(error) = &row.error {
                 if *details_enable {
                     print!("{} ", row.hostname_port
                         .as_ref()
                         .unwrap()
                     );
                 };
-                println!("{:#?}", row.error
-                    .as_ref()
-                    .unwrap()
-                );
+                println!("{:#?}", error);
             };
         }
         Ok(())
@@ -428,12 +425,12 @@ impl MastersDiff {
             debug!("uuid: {}", permanent_uuid);
             // first check: are both situations equal?
             // if so, don't display anything: this is a diff.
-            #[allow(clippy::nonminimal_bool)]
             if row.first_instance_seqno == row.second_instance_seqno
                 && row.first_start_time_us == row.second_start_time_us
                 && row.first_placement_cloud == row.second_placement_cloud
                 && row.first_placement_region == row.second_placement_region
-                && row.first_placement_cloud == row.second_placement_cloud
+                && row.first_placement_zone == row.second_placement_zone
+                && row.first_placement_uuid == row.second_placement_uuid
                 && row.first_role == row.second_role
                 && row.first_private_rpc_addresses == row.second_private_rpc_addresses
                 && row.first_http_addresses == row.second_http_addresses
@@ -1,9 +1,14 @@
 //! Module for reading the /memz output for the master and tablet server.
 //!
 use std::{fs, io::Write, sync::mpsc::channel, time::Instant, env};
+use chrono::Local;
+use regex::Regex;
 use log::*;
 use anyhow::{Result, Context};
+use crate::snapshot;
 use crate::utility;
+use crate::mems::{Mem, AllMems, MemsDiff, MemsDiffFields};
+use crate::Opts;
 
 pub struct Mems;
 
@@ -62,6 +67,7 @@ impl Mems {
                 let mut file = fs::OpenOptions::new()
                     .create(true)
                     .write(true)
+                    .truncate(true)
                     .open(mems_file)
                     .with_context(|| format!("Cannot create file: {}", mems_file.display()))?;
 
@@ -80,11 +86,331 @@ impl Mems {
     }
 }
 
+/// Parse the tcmalloc statistics from the `/memz` output.
+///
+/// The tcmalloc statistics look like this:
+/// ```text
+/// ------------------------------------------------
+/// MALLOC:       82470992 (   78.7 MiB) Bytes in use by application
+/// MALLOC: +     13172736 (   12.6 MiB) Bytes in page heap freelist
+/// MALLOC: +      2144528 (    2.0 MiB) Bytes in central cache freelist
+/// MALLOC: +      2288640 (    2.2 MiB) Bytes in transfer cache freelist
+/// MALLOC: +      4143040 (    4.0 MiB) Bytes in thread cache freelists
+/// MALLOC: +      2883584 (    2.8 MiB) Bytes in malloc metadata
+/// MALLOC:   ------------
+/// MALLOC: =    107103520 (  102.1 MiB) Actual memory used (physical + swap)
+/// MALLOC: +     16015360 (   15.3 MiB) Bytes released to OS (aka unmapped)
+/// MALLOC:   ------------
+/// MALLOC: =    123118880 (  117.4 MiB) Virtual address space used
+/// MALLOC:
+/// MALLOC:           4703              Spans in use
+/// MALLOC:             53              Thread heaps in use
+/// MALLOC:           8192              Tcmalloc page size
+/// ------------------------------------------------
+/// ```
+/// If no statistics are found, None is returned.
+pub fn parse_mems(
+    hostname_port: &str,
+    mems_data: &str,
+) -> Option<Mem>
+{
+    let malloc_line = Regex::new(r"^MALLOC:\s*[+=]?\s*(\d+)\s+(?:\(\s*[\d.]+ [KMGT]?i?B\)\s+)?(.+?)\s*$").unwrap();
+    let mut mem = Mem { hostname_port: hostname_port.to_string(), ..Default::default() };
+    let mut found = false;
+    for captures in mems_data.lines().filter_map(|line| malloc_line.captures(line))
+    {
+        let bytes = captures[1].parse::<i64>().unwrap_or_default();
+        let field = match &captures[2] {
+            "Bytes in use by application" => &mut mem.bytes_in_use_by_application,
+            "Bytes in page heap freelist" => &mut mem.page_heap_freelist,
+            "Bytes in central cache freelist" => &mut mem.central_cache_freelist,
+            "Bytes in transfer cache freelist" => &mut mem.transfer_cache_freelist,
+            "Bytes in thread cache freelists" => &mut mem.thread_cache_freelists,
+            "Bytes in malloc metadata" => &mut mem.malloc_metadata,
+            "Actual memory used (physical + swap)" => &mut mem.actual_memory_used,
+            "Bytes released to OS (aka unmapped)" => &mut mem.released_to_os,
+            "Virtual address space used" => &mut mem.virtual_address_space_used,
+            "Spans in use" => &mut mem.spans_in_use,
+            "Thread heaps in use" => &mut mem.thread_heaps_in_use,
+            "Tcmalloc page size" => &mut mem.tcmalloc_page_size,
+            other => {
+                debug!("Unknown tcmalloc statistic: {}", other);
+                continue;
+            },
+        };
+        *field = bytes;
+        found = true;
+    }
+    found.then_some(mem)
+}
+
+impl Mem {
+    /// The percentage of the memory used by tcmalloc that is not in use by the application.
+    pub fn fragmentation_percentage(&self) -> f64
+    {
+        if self.actual_memory_used == 0
+        {
+            0_f64
+        }
+        else
+        {
+            (self.actual_memory_used - self.bytes_in_use_by_application) as f64 / self.actual_memory_used as f64 * 100_f64
+        }
+    }
+}
+
+impl AllMems {
+    pub fn new() -> Self { Default::default() }
+    pub async fn read_mems(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> AllMems
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel).build().unwrap();
+        let (tx, rx) = channel();
+
+        pool.scope(move |s| {
+            for host in hosts {
+                for port in ports {
+                    let tx = tx.clone();
+                    s.spawn(move |_| {
+                        let detail_snapshot_time = Local::now();
+                        let mut mem = parse_mems(&format!("{}:{}", host, port), &Mems::read_http(host, port));
+                        mem.iter_mut().for_each(|r| r.timestamp = detail_snapshot_time);
+                        tx.send(mem).expect("error sending data via tx (mems)");
+                    });
+                }
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        let mut allmems = AllMems::new();
+        allmems.mems = rx.iter().flatten().collect();
+        allmems.mems.sort_by(|a, b| a.hostname_port.cmp(&b.hostname_port));
+        allmems
+    }
+    /// Read the mems files of a snapshot, and parse the tcmalloc statistics.
+    /// The files do not contain a timestamp, so the snapshot timestamp is used.
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<AllMems>
+    {
+        let snapshot_time = snapshot::Snapshot::read_snapshot_timestamp(snapshot_number)?;
+        let snapshot_directory = snapshot::snapshot_directory(snapshot_number)?;
+        let mut allmems = AllMems::new();
+        for entry in fs::read_dir(&snapshot_directory)
+            .with_context(|| format!("Error reading snapshot directory: {}", snapshot_directory.display()))?
+            .filter_map(|entry| entry.ok())
+        {
+            let filename = entry.file_name().into_string().unwrap_or_default();
+            if let Some(hostname_port) = filename.strip_prefix("mems_").and_then(|filename| filename.strip_suffix(".txt"))
+            {
+                let mems_data = fs::read_to_string(entry.path())
+                    .with_context(|| format!("Error reading file: {}", entry.path().display()))?;
+                if let Some(mut mem) = parse_mems(hostname_port, &mems_data)
+                {
+                    mem.timestamp = snapshot_time;
+                    allmems.mems.push(mem);
+                }
+            }
+        }
+        allmems.mems.sort_by(|a, b| a.hostname_port.cmp(&b.hostname_port));
+        Ok(allmems)
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        println!("{:20} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>7}",
+                 "hostname_port",
+                 "in_use",
+                 "pageheap_free",
+                 "unmapped",
+                 "central_free",
+                 "transfer_free",
+                 "thread_free",
+                 "metadata",
+                 "actual_used",
+                 "%frag");
+        for mem in self.mems.iter().filter(|mem| hostname_filter.is_match(&mem.hostname_port))
+        {
+            println!("{:20} {:14} {:14} {:14} {:14} {:14} {:14} {:14} {:14} {:7.2}",
+                     mem.hostname_port,
+                     mem.bytes_in_use_by_application,
+                     mem.page_heap_freelist,
+                     mem.released_to_os,
+                     mem.central_cache_freelist,
+                     mem.transfer_cache_freelist,
+                     mem.thread_cache_freelists,
+                     mem.malloc_metadata,
+                     mem.actual_memory_used,
+                     mem.fragmentation_percentage(),
+            );
+        }
+    }
+}
+
+impl MemsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<MemsDiff>
+    {
+        let mut memsdiff = MemsDiff::new();
+        memsdiff.first_snapshot(AllMems::read_snapshot(begin_snapshot)?);
+        memsdiff.second_snapshot(AllMems::read_snapshot(end_snapshot)?);
+        Ok(memsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allmems: AllMems,
+    )
+    {
+        for mem in allmems.mems
+        {
+            self.btreememsdiff.insert(mem.hostname_port.clone(), MemsDiffFields { first_snapshot: mem, ..Default::default() });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allmems: AllMems,
+    )
+    {
+        for mem in allmems.mems
+        {
+            let hostname_port = mem.hostname_port.clone();
+            self.btreememsdiff.entry(hostname_port).or_default().second_snapshot = mem;
+        }
+    }
+    /// Print the difference of the tcmalloc statistics per host, and the fragmentation percentage of both snapshots.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        println!("{:20} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14} {:>7} {:>7}",
+                 "hostname_port",
+                 "in_use",
+                 "pageheap_free",
+                 "unmapped",
+                 "central_free",
+                 "transfer_free",
+                 "thread_free",
+                 "metadata",
+                 "actual_used",
+                 "%frag_1",
+                 "%frag_2");
+        for (hostname_port, row) in self.btreememsdiff.iter().filter(|(hostname_port, _)| hostname_filter.is_match(hostname_port))
+        {
+            let (first, second) = (&row.first_snapshot, &row.second_snapshot);
+            println!("{:20} {:+14} {:+14} {:+14} {:+14} {:+14} {:+14} {:+14} {:+14} {:7.2} {:7.2}",
+                     hostname_port,
+                     second.bytes_in_use_by_application - first.bytes_in_use_by_application,
+                     second.page_heap_freelist - first.page_heap_freelist,
+                     second.released_to_os - first.released_to_os,
+                     second.central_cache_freelist - first.central_cache_freelist,
+                     second.transfer_cache_freelist - first.transfer_cache_freelist,
+                     second.thread_cache_freelists - first.thread_cache_freelists,
+                     second.malloc_metadata - first.malloc_metadata,
+                     second.actual_memory_used - first.actual_memory_used,
+                     first.fragmentation_percentage(),
+                     second.fragmentation_percentage(),
+            );
+        }
+    }
+}
+
+pub async fn print_mems(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let allmems = match options.print_mems.as_ref().unwrap() {
+        Some(snapshot_number) => AllMems::read_snapshot(snapshot_number)?,
+        None => AllMems::read_mems(&hosts, &ports, parallel).await,
+    };
+    allmems.print(&hostname_filter);
+    Ok(())
+}
+
+pub async fn mems_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+    let end_snapshot_time = snapshot::Snapshot::read_snapshot_timestamp(&end_snapshot)?;
+
+    let mems_diff = MemsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    snapshot::print_time_between_snapshots(&begin_snapshot_row.timestamp, &end_snapshot_time);
+    mems_diff.print(&hostname_filter);
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     //use crate::utility_test::*;
 
+    #[test]
+    fn unit_parse_mems_data() {
+        let mems = r#"------------------------------------------------
+MALLOC:       82470992 (   78.7 MiB) Bytes in use by application
+MALLOC: +     13172736 (   12.6 MiB) Bytes in page heap freelist
+MALLOC: +      2144528 (    2.0 MiB) Bytes in central cache freelist
+MALLOC: +      2288640 (    2.2 MiB) Bytes in transfer cache freelist
+MALLOC: +      4143040 (    4.0 MiB) Bytes in thread cache freelists
+MALLOC: +      2883584 (    2.8 MiB) Bytes in malloc metadata
+MALLOC:   ------------
+MALLOC: =    107103520 (  102.1 MiB) Actual memory used (physical + swap)
+MALLOC: +     16015360 (   15.3 MiB) Bytes released to OS (aka unmapped)
+MALLOC:   ------------
+MALLOC: =    123118880 (  117.4 MiB) Virtual address space used
+MALLOC:
+MALLOC:           4703              Spans in use
+MALLOC:             53              Thread heaps in use
+MALLOC:           8192              Tcmalloc page size
+------------------------------------------------
+Call ReleaseFreeMemory() to release freelist memory to the OS (via madvise()).
+Bytes released to the OS take up virtual address space but no physical memory.
+"#;
+        let mem = parse_mems("yb-1.local:9000", mems).unwrap();
+        assert_eq!(mem.hostname_port, "yb-1.local:9000");
+        assert_eq!(mem.bytes_in_use_by_application, 82470992);
+        assert_eq!(mem.page_heap_freelist, 13172736);
+        assert_eq!(mem.central_cache_freelist, 2144528);
+        assert_eq!(mem.transfer_cache_freelist, 2288640);
+        assert_eq!(mem.thread_cache_freelists, 4143040);
+        assert_eq!(mem.malloc_metadata, 2883584);
+        assert_eq!(mem.actual_memory_used, 107103520);
+        assert_eq!(mem.released_to_os, 16015360);
+        assert_eq!(mem.virtual_address_space_used, 123118880);
+        assert_eq!(mem.spans_in_use, 4703);
+        assert_eq!(mem.thread_heaps_in_use, 53);
+        assert_eq!(mem.tcmalloc_page_size, 8192);
+        assert_eq!(format!("{:.2}", mem.fragmentation_percentage()), "23.00");
+
+        assert_eq!(parse_mems("yb-1.local:9000", "no tcmalloc statistics"), None);
+    }
+
     #[tokio::test]
     async fn integration_parse_mems_tserver()
     {
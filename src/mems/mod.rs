@@ -3,11 +3,15 @@
 //! The `/memz` endpoint contains two memory overviews.
 //! It is available on all master and tablet server endpoints, default port numbers 7000 (master), 9000, 12000 (tablet server).
 //!
-//! yb_stats doesn't do anything other than:
+//! For the snapshot, yb_stats doesn't do anything other than:
 //! - perform a HTTP Get and collect the result from the endpoint.
 //! - check if the returned data start with "------------------------------------------------".
 //! - If so, save the result as "mems_*hostname:port*"
 //!
+//! For printing and the diff, the tcmalloc statistics (the `MALLOC:` lines) are parsed into the [Mem] struct.
+//!
+mod structs;
 mod functions;
 
+pub use structs::*;
 pub use functions::*;
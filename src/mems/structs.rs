@@ -0,0 +1,47 @@
+//! The structs
+//!
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
+
+/// The tcmalloc statistics of a host, parsed from the `MALLOC:` lines of `/memz`.
+/// All sizes are in bytes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Mem {
+    pub hostname_port: String,
+    /// yb_stats added to allow understanding the snapshot timestamp.
+    pub timestamp: DateTime<Local>,
+    pub bytes_in_use_by_application: i64,
+    pub page_heap_freelist: i64,
+    pub central_cache_freelist: i64,
+    pub transfer_cache_freelist: i64,
+    pub thread_cache_freelists: i64,
+    pub malloc_metadata: i64,
+    /// Physical memory used by tcmalloc: in use by the application, free in the tcmalloc caches and metadata.
+    pub actual_memory_used: i64,
+    /// Page heap memory that is released to the OS, and thus takes up virtual address space only.
+    pub released_to_os: i64,
+    pub virtual_address_space_used: i64,
+    pub spans_in_use: i64,
+    pub thread_heaps_in_use: i64,
+    pub tcmalloc_page_size: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct AllMems {
+    pub mems: Vec<Mem>,
+}
+// diff
+/// BTreeMap for storing a mems diff struct per `hostname_port`.
+type BTreeMemsDiff = BTreeMap<String, MemsDiffFields>;
+/// The wrapper struct for holding the btreemap holding the diff structs.
+#[derive(Debug, Default)]
+pub struct MemsDiff {
+    pub btreememsdiff: BTreeMemsDiff,
+}
+/// The tcmalloc statistics of a host in the first and the second snapshot.
+/// A host that is missing in one of the snapshots has the statistics of that snapshot set to zero.
+#[derive(Debug, Default)]
+pub struct MemsDiffFields {
+    pub first_snapshot: Mem,
+    pub second_snapshot: Mem,
+}
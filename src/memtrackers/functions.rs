@@ -1,13 +1,14 @@
 //! The impls and functions
 //!
-use chrono::Local;
+use chrono::{DateTime, Local};
 use regex::Regex;
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::BTreeMap, sync::mpsc::channel, time::Instant};
+use colored::*;
 use scraper::{Html, Selector};
 use log::*;
 use anyhow::Result;
 use crate::snapshot;
-use crate::memtrackers::{MemTrackers, AllMemTrackers};
+use crate::memtrackers::{MemTrackers, AllMemTrackers, MemTrackerNode, MemTrackerConsumer, MemTrackersDiff, MemTrackersDiffFields};
 use crate::Opts;
 use crate::utility;
 
@@ -148,40 +149,383 @@ impl AllMemTrackers {
 
         memtrackers
     }
+    /// Build the memtrackers tree of every host from the order and depth of the memtrackers.
+    /// The nodes are returned in the original order, so every node is followed by its children.
+    pub fn tree(&self) -> Vec<MemTrackerNode>
+    {
+        let mut nodes: Vec<MemTrackerNode> = Vec::with_capacity(self.memtrackers.len());
+        // the indexes in nodes of the parents of the current memtracker.
+        let mut parents: Vec<usize> = Vec::new();
+        let paths = memtracker_paths(&self.memtrackers);
+        for (row, path) in self.memtrackers.iter().zip(paths)
+        {
+            let depth = row.depth.parse::<usize>().unwrap_or_default();
+            if nodes.last().is_some_and(|node| node.hostname_port != row.hostname_port)
+            {
+                parents.clear();
+            }
+            parents.truncate(depth);
+            let current_consumption = parse_size(&row.current_consumption).unwrap_or_default();
+            if let Some(parent) = parents.last()
+            {
+                nodes[*parent].children_consumption += current_consumption;
+            }
+            parents.iter().for_each(|parent| nodes[*parent].descendants += 1);
+            parents.push(nodes.len());
+            nodes.push(MemTrackerNode {
+                hostname_port: row.hostname_port.clone(),
+                timestamp: row.timestamp,
+                path,
+                id: row.id.clone(),
+                depth,
+                current_consumption,
+                peak_consumption: parse_size(&row.peak_consumption).unwrap_or_default(),
+                limit: parse_size(&row.limit),
+                ..Default::default()
+            });
+        }
+        nodes
+    }
+    /// Print the memtrackers tree per host, with the sum of the consumption of the children of every memtracker.
+    /// If max_depth is set, the memtrackers below max_depth are collapsed into their parent, which shows the number of hidden memtrackers.
     pub fn print(
         &self,
         hostname_filter: &Regex,
         stat_name_filter: &Regex,
+        max_depth: &Option<usize>,
     ) -> Result<()>
     {
         info!("print_memtrackers");
 
         let mut previous_hostname_port = String::from("");
-        for row in &self.memtrackers
+        for node in self.tree()
+            .iter()
+            .filter(|node| hostname_filter.is_match(&node.hostname_port)
+                && stat_name_filter.is_match(&node.path)
+                && max_depth.is_none_or(|max_depth| node.depth <= max_depth))
         {
-            if hostname_filter.is_match(&row.hostname_port.clone())
-                && stat_name_filter.is_match(&row.id)
+            if node.hostname_port != previous_hostname_port
             {
-                if row.hostname_port.clone() != previous_hostname_port
-                {
-                    println!("{}", "-".repeat(174));
-                    println!("Host: {}, Snapshot time: {}", &row.hostname_port.clone(), row.timestamp);
-                    println!("{}", "-".repeat(174));
-                    println!("{:20} {:90} {:>20} {:>20} {:>20}",
-                             "hostname_port",
-                             "id",
-                             "current_consumption",
-                             "peak_consumption",
-                             "limit");
-                    println!("{}", "-".repeat(174));
-                    previous_hostname_port = row.hostname_port.clone();
-                }
-                let indented_id = " ".repeat(row.depth.parse::<usize>().unwrap()) + &row.id;
-                println!("{:20} {:90} {:>20} {:>20} {:>20}", row.hostname_port.clone(), indented_id, row.current_consumption, row.peak_consumption, row.limit)
+                println!("{}", "-".repeat(174));
+                println!("Host: {}, Snapshot time: {}", &node.hostname_port, node.timestamp);
+                println!("{}", "-".repeat(174));
+                println!("{:20} {:70} {:>15} {:>15} {:>15} {:>15} {:>10}",
+                         "hostname_port",
+                         "id",
+                         "current",
+                         "children",
+                         "peak",
+                         "limit",
+                         "collapsed");
+                println!("{}", "-".repeat(174));
+                previous_hostname_port = node.hostname_port.clone();
             }
+            let collapsed = *max_depth == Some(node.depth) && node.descendants > 0;
+            println!("{:20} {:70} {:>15} {:>15} {:>15} {:>15} {:>10}",
+                     node.hostname_port,
+                     " ".repeat(node.depth) + &node.id,
+                     format_size(node.current_consumption),
+                     if node.descendants > 0 { format_size(node.children_consumption) } else { String::new() },
+                     format_size(node.peak_consumption),
+                     node.limit.map(format_size).unwrap_or_else(|| "none".to_string()),
+                     if collapsed { format!("+{}", node.descendants) } else { String::new() },
+            );
         }
         Ok(())
     }
+    /// Return the top_n memtrackers that use the most memory compared with the same memtracker path on the other hosts,
+    /// which is the ratio of the current consumption to the average current consumption on the other hosts.
+    /// A memtracker that the other hosts do not use, or that is not on other hosts, has the highest ratio.
+    /// Memtrackers with the same ratio are ordered by current consumption.
+    /// Memtrackers that use less than MEMTRACKER_TOP_MINIMUM_CONSUMPTION are not returned.
+    pub fn top_consumers(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        top_n: usize,
+    ) -> Vec<MemTrackerConsumer>
+    {
+        let mut paths: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+        for node in self.tree()
+            .into_iter()
+            .filter(|node| hostname_filter.is_match(&node.hostname_port) && stat_name_filter.is_match(&node.path))
+        {
+            *paths.entry(node.path).or_default().entry(node.hostname_port).or_default() += node.current_consumption;
+        }
+        let mut consumers: Vec<MemTrackerConsumer> = Vec::new();
+        for (path, hosts) in &paths
+        {
+            let total: i64 = hosts.values().sum();
+            for (hostname_port, current_consumption) in hosts.iter().filter(|(_, current_consumption)| **current_consumption >= MEMTRACKER_TOP_MINIMUM_CONSUMPTION)
+            {
+                let peers = hosts.len() - 1;
+                consumers.push(MemTrackerConsumer {
+                    hostname_port: hostname_port.clone(),
+                    path: path.clone(),
+                    current_consumption: *current_consumption,
+                    peers,
+                    peers_average: if peers == 0 { 0_f64 } else { (total - current_consumption) as f64 / peers as f64 },
+                });
+            }
+        }
+        consumers.sort_by(|a, b| b.peers_ratio().unwrap_or(f64::INFINITY).total_cmp(&a.peers_ratio().unwrap_or(f64::INFINITY))
+            .then(b.current_consumption.cmp(&a.current_consumption)));
+        consumers.truncate(top_n);
+        consumers
+    }
+    /// Print the top_n memtrackers that use the most memory compared with the same memtracker path on the other hosts.
+    pub fn print_top_consumers(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        top_n: usize,
+    )
+    {
+        println!("{:20} {:70} {:>15} {:>6} {:>15} {:>10}",
+                 "hostname_port",
+                 "path",
+                 "current",
+                 "peers",
+                 "peers_average",
+                 "x_average");
+        for consumer in self.top_consumers(hostname_filter, stat_name_filter, top_n)
+        {
+            println!("{:20} {:70} {:>15} {:6} {:>15} {:>10}",
+                     consumer.hostname_port,
+                     consumer.path,
+                     format_size(consumer.current_consumption),
+                     consumer.peers,
+                     if consumer.peers > 0 { format_size(consumer.peers_average.round() as i64) } else { String::new() },
+                     consumer.peers_ratio().map(|ratio| format!("{:.2}", ratio)).unwrap_or_default(),
+            );
+        }
+    }
+}
+
+impl MemTrackerConsumer {
+    /// The ratio of the current consumption to the average current consumption on the other hosts,
+    /// or None if the other hosts do not use the memtracker.
+    pub fn peers_ratio(&self) -> Option<f64>
+    {
+        (self.peers_average > 0_f64).then(|| self.current_consumption as f64 / self.peers_average)
+    }
+}
+
+/// The current consumption a memtracker must have to be shown as a top consumer.
+const MEMTRACKER_TOP_MINIMUM_CONSUMPTION: i64 = 1024 * 1024;
+
+/// The percentage of the limit a memtracker must use to be flagged as approaching its limit.
+const MEMTRACKER_LIMIT_WARNING_PERCENTAGE: f64 = 90.0;
+
+/// Parse a memtracker size as shown by /mem-trackers, such as "0B", "2.0K" or "1.23G", into bytes.
+///
+/// The current consumption of a memtracker can be followed by the consumption including the children in brackets,
+/// such as "43.15M (58.42M)", only the first figure is used.
+/// A size that cannot be parsed, such as the limit "none", returns None.
+pub fn parse_size(size: &str) -> Option<i64>
+{
+    let size = size.split_whitespace().next()?;
+    let (number, multiplier) = match size.chars().last()? {
+        'B' => (&size[..size.len() - 1], 1_f64),
+        'K' => (&size[..size.len() - 1], 1024_f64),
+        'M' => (&size[..size.len() - 1], 1024_f64.powi(2)),
+        'G' => (&size[..size.len() - 1], 1024_f64.powi(3)),
+        'T' => (&size[..size.len() - 1], 1024_f64.powi(4)),
+        'P' => (&size[..size.len() - 1], 1024_f64.powi(5)),
+        _ => (size, 1_f64),
+    };
+    number.parse::<f64>().ok().map(|number| (number * multiplier).round() as i64)
+}
+
+/// Format a size in bytes in the way /mem-trackers shows it, such as "0B", "2.00K" or "1.23G".
+pub fn format_size(size: i64) -> String
+{
+    let units = ['K', 'M', 'G', 'T', 'P'];
+    let mut number = size as f64;
+    let mut unit = None;
+    for next_unit in units
+    {
+        if number.abs() < 1024_f64 { break; }
+        number /= 1024_f64;
+        unit = Some(next_unit);
+    }
+    match unit {
+        Some(unit) => format!("{:.2}{}", number, unit),
+        None => format!("{}B", size),
+    }
+}
+
+/// Build the path of every memtracker, such as "root->server->BlockBasedTable", from the order and depth of the memtrackers.
+///
+/// The memtrackers of a host are stored in the order of the /mem-trackers page, which shows the children of a memtracker
+/// directly after it with a depth one higher.
+pub fn memtracker_paths(memtrackers: &[MemTrackers]) -> Vec<String>
+{
+    let mut paths = Vec::with_capacity(memtrackers.len());
+    let mut parents: Vec<String> = Vec::new();
+    let mut previous_hostname_port = String::new();
+    for row in memtrackers
+    {
+        if row.hostname_port != previous_hostname_port
+        {
+            parents.clear();
+            previous_hostname_port = row.hostname_port.clone();
+        }
+        let depth = row.depth.parse::<usize>().unwrap_or_default();
+        parents.truncate(depth);
+        parents.push(row.id.clone());
+        paths.push(parents.join("->"));
+    }
+    paths
+}
+
+impl MemTrackersDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+        begin_snapshot_time: &DateTime<Local>,
+    ) -> Result<MemTrackersDiff>
+    {
+        let mut memtrackersdiff = MemTrackersDiff::new();
+
+        let mut allmemtrackers = AllMemTrackers::new();
+        allmemtrackers.memtrackers = snapshot::read_snapshot_json(begin_snapshot, "memtrackers")?;
+        memtrackersdiff.first_snapshot(allmemtrackers);
+
+        let mut allmemtrackers = AllMemTrackers::new();
+        allmemtrackers.memtrackers = snapshot::read_snapshot_json(end_snapshot, "memtrackers")?;
+        memtrackersdiff.second_snapshot(allmemtrackers, begin_snapshot_time);
+
+        Ok(memtrackersdiff)
+    }
+    /// Memtrackers with the same path on the same host, which can happen for memtrackers that are not unique,
+    /// are added up.
+    fn first_snapshot(
+        &mut self,
+        allmemtrackers: AllMemTrackers,
+    )
+    {
+        let paths = memtracker_paths(&allmemtrackers.memtrackers);
+        for (row, path) in allmemtrackers.memtrackers.into_iter().zip(paths)
+        {
+            let memtrackersdifffields = self.btreememtrackersdiff
+                .entry((row.hostname_port.clone(), path))
+                .or_insert_with(|| MemTrackersDiffFields { first_snapshot_time: row.timestamp, ..Default::default() });
+            memtrackersdifffields.first_current_consumption += parse_size(&row.current_consumption).unwrap_or_default();
+            memtrackersdifffields.first_peak_consumption += parse_size(&row.peak_consumption).unwrap_or_default();
+        }
+    }
+    /// A memtracker that was not present in the first snapshot is considered to start from zero at the first snapshot time.
+    fn second_snapshot(
+        &mut self,
+        allmemtrackers: AllMemTrackers,
+        first_snapshot_time: &DateTime<Local>,
+    )
+    {
+        let paths = memtracker_paths(&allmemtrackers.memtrackers);
+        for (row, path) in allmemtrackers.memtrackers.into_iter().zip(paths)
+        {
+            let memtrackersdifffields = self.btreememtrackersdiff
+                .entry((row.hostname_port.clone(), path))
+                .or_insert_with(|| MemTrackersDiffFields { first_snapshot_time: *first_snapshot_time, ..Default::default() });
+            memtrackersdifffields.second_snapshot_time = row.timestamp;
+            memtrackersdifffields.second_current_consumption += parse_size(&row.current_consumption).unwrap_or_default();
+            memtrackersdifffields.second_peak_consumption += parse_size(&row.peak_consumption).unwrap_or_default();
+            memtrackersdifffields.limit = parse_size(&row.limit);
+        }
+    }
+    /// The snapshot time of a host is the second snapshot time of its root memtracker.
+    /// A host that is not in the second snapshot has no second snapshot time, so it gets the end snapshot time.
+    fn host_snapshot_time(
+        &self,
+        hostname_port: &str,
+        end_snapshot_time: &DateTime<Local>,
+    ) -> DateTime<Local>
+    {
+        self.btreememtrackersdiff
+            .get(&(hostname_port.to_string(), "root".to_string()))
+            .map(|row| row.second_snapshot_time)
+            .filter(|second_snapshot_time| *second_snapshot_time != DateTime::<Local>::default())
+            .unwrap_or(*end_snapshot_time)
+    }
+    /// Print the growth of the memtrackers that changed between the snapshots per host, or all memtrackers if details_enable is set.
+    /// The first row of every host is the root memtracker, which shows the growth of the host.
+    /// Memtrackers that use MEMTRACKER_LIMIT_WARNING_PERCENTAGE or more of their limit are flagged, and always shown.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        details_enable: &bool,
+        end_snapshot_time: &DateTime<Local>,
+    )
+    {
+        let mut previous_hostname_port = String::from("");
+        for ((hostname_port, path), row) in self.btreememtrackersdiff
+            .iter()
+            .filter(|((hostname_port, path), _)| hostname_filter.is_match(hostname_port) && stat_name_filter.is_match(path))
+        {
+            let limit_percentage = row.limit
+                .filter(|limit| *limit > 0)
+                .map(|limit| row.second_current_consumption as f64 / limit as f64 * 100_f64);
+            let near_limit = limit_percentage.is_some_and(|percentage| percentage >= MEMTRACKER_LIMIT_WARNING_PERCENTAGE);
+            let difference = row.second_current_consumption - row.first_current_consumption;
+            if difference == 0 && !near_limit && !*details_enable
+            {
+                continue;
+            }
+            if *hostname_port != previous_hostname_port
+            {
+                println!("{}", "-".repeat(174));
+                println!("Host: {}, Snapshot time: {}", hostname_port, self.host_snapshot_time(hostname_port, end_snapshot_time));
+                println!("{}", "-".repeat(174));
+                println!("{:20} {:70} {:>15} {:>15} {:>15} {:>15} {:>15} {:>6}",
+                         "hostname_port",
+                         "path",
+                         "first_current",
+                         "second_current",
+                         "difference",
+                         "second_peak",
+                         "limit",
+                         "%limit");
+                println!("{}", "-".repeat(174));
+                previous_hostname_port = hostname_port.clone();
+            }
+            println!("{:20} {:70} {:>15} {:>15} {:>15} {:>15} {:>15} {:>6} {}",
+                     hostname_port,
+                     path,
+                     format_size(row.first_current_consumption),
+                     format_size(row.second_current_consumption),
+                     format!("{}{}", if difference > 0 { "+" } else { "" }, format_size(difference)),
+                     format_size(row.second_peak_consumption),
+                     row.limit.map(format_size).unwrap_or_else(|| "none".to_string()),
+                     limit_percentage.map(|percentage| format!("{:.1}", percentage)).unwrap_or_default(),
+                     if near_limit { "near limit".red() } else { "".normal() },
+            );
+        }
+    }
+}
+
+pub async fn memtrackers_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let end_snapshot_time = snapshot::Snapshot::read_snapshot_timestamp(&end_snapshot)?;
+
+    let memtrackers_diff = MemTrackersDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
+    memtrackers_diff.print(&hostname_filter, &stat_name_filter, &options.details_enable, &end_snapshot_time);
+
+    Ok(())
 }
 
 pub async fn print_memtrackers(
@@ -194,16 +538,17 @@ pub async fn print_memtrackers(
     let hostname_filter = utility::set_regex(&options.hostname_match);
     let stat_name_filter = utility::set_regex(&options.stat_name_match);
 
-    match options.print_memtrackers.as_ref().unwrap() {
+    let allmemtrackers = match options.print_memtrackers.as_ref().unwrap() {
         Some(snapshot_number) => {
             let mut allmemtrackers = AllMemTrackers::new();
             allmemtrackers.memtrackers = snapshot::read_snapshot_json(snapshot_number, "memtrackers")?;
-            allmemtrackers.print(&hostname_filter, &stat_name_filter)?;
-        },
-        None => {
-            let allmemtrackers = AllMemTrackers::read_memtrackers(&hosts, &ports, parallel).await;
-            allmemtrackers.print(&hostname_filter, &stat_name_filter)?;
+            allmemtrackers
         },
+        None => AllMemTrackers::read_memtrackers(&hosts, &ports, parallel).await,
+    };
+    match options.memtrackers_top {
+        Some(top_n) => allmemtrackers.print_top_consumers(&hostname_filter, &stat_name_filter, top_n),
+        None => allmemtrackers.print(&hostname_filter, &stat_name_filter, &options.memtrackers_depth)?,
     }
     Ok(())
 }
@@ -212,6 +557,150 @@ pub async fn print_memtrackers(
 mod tests {
     use super::*;
 
+    #[test]
+    fn unit_memtrackers_parse_size() {
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("28B"), Some(28));
+        assert_eq!(parse_size("2.0K"), Some(2048));
+        assert_eq!(parse_size("1.00G"), Some(1073741824));
+        assert_eq!(parse_size("43.15M (58.42M)"), Some(45246054));
+        assert_eq!(parse_size("none"), None);
+        assert_eq!(parse_size(""), None);
+    }
+
+    #[test]
+    fn unit_memtrackers_diff() {
+        let memtracker = |id: &str, depth: &str, current_consumption: &str, limit: &str| MemTrackers {
+            hostname_port: "yb-1.local:9000".to_string(),
+            id: id.to_string(),
+            current_consumption: current_consumption.to_string(),
+            peak_consumption: current_consumption.to_string(),
+            limit: limit.to_string(),
+            depth: depth.to_string(),
+            ..Default::default()
+        };
+        let first = AllMemTrackers { memtrackers: vec![
+            memtracker("root", "0", "100.00M", "1.00G"),
+            memtracker("server", "1", "50.00M", "none"),
+            memtracker("MemTable", "2", "10.00M", "none"),
+            memtracker("BlockBasedTable", "1", "10.00M", "12.00M"),
+        ]};
+        let second = AllMemTrackers { memtrackers: vec![
+            memtracker("root", "0", "120.00M", "1.00G"),
+            memtracker("server", "1", "70.00M", "none"),
+            memtracker("MemTable", "2", "30.00M", "none"),
+            memtracker("BlockBasedTable", "1", "11.00M", "12.00M"),
+        ]};
+        assert_eq!(memtracker_paths(&first.memtrackers), vec!["root", "root->server", "root->server->MemTable", "root->BlockBasedTable"]);
+
+        let mut memtrackersdiff = MemTrackersDiff::new();
+        memtrackersdiff.first_snapshot(first);
+        memtrackersdiff.second_snapshot(second, &Local::now());
+        let memtable = &memtrackersdiff.btreememtrackersdiff[&("yb-1.local:9000".to_string(), "root->server->MemTable".to_string())];
+        assert_eq!(memtable.second_current_consumption - memtable.first_current_consumption, 20 * 1024 * 1024);
+        assert_eq!(memtable.limit, None);
+        let blockbasedtable = &memtrackersdiff.btreememtrackersdiff[&("yb-1.local:9000".to_string(), "root->BlockBasedTable".to_string())];
+        assert_eq!(blockbasedtable.limit, Some(12 * 1024 * 1024));
+    }
+
+    #[test]
+    fn unit_memtrackers_diff_host_snapshot_time() {
+        let first_snapshot_time = Local::now();
+        let second_snapshot_time = first_snapshot_time + chrono::Duration::seconds(10);
+        let end_snapshot_time = second_snapshot_time + chrono::Duration::seconds(1);
+        let memtracker = |hostname_port: &str, id: &str, depth: &str, timestamp: DateTime<Local>| MemTrackers {
+            hostname_port: hostname_port.to_string(),
+            timestamp,
+            id: id.to_string(),
+            current_consumption: "1.00M".to_string(),
+            peak_consumption: "1.00M".to_string(),
+            limit: "none".to_string(),
+            depth: depth.to_string(),
+        };
+        // yb-2 is not in the second snapshot.
+        let first = AllMemTrackers { memtrackers: vec![
+            memtracker("yb-1.local:9000", "root", "0", first_snapshot_time),
+            memtracker("yb-1.local:9000", "server", "1", first_snapshot_time),
+            memtracker("yb-2.local:9000", "root", "0", first_snapshot_time),
+            memtracker("yb-2.local:9000", "server", "1", first_snapshot_time),
+        ]};
+        let second = AllMemTrackers { memtrackers: vec![
+            memtracker("yb-1.local:9000", "root", "0", second_snapshot_time),
+            memtracker("yb-1.local:9000", "server", "1", second_snapshot_time),
+        ]};
+        let mut memtrackersdiff = MemTrackersDiff::new();
+        memtrackersdiff.first_snapshot(first);
+        memtrackersdiff.second_snapshot(second, &first_snapshot_time);
+        assert_eq!(memtrackersdiff.host_snapshot_time("yb-1.local:9000", &end_snapshot_time), second_snapshot_time);
+        assert_eq!(memtrackersdiff.host_snapshot_time("yb-2.local:9000", &end_snapshot_time), end_snapshot_time);
+    }
+
+    #[test]
+    fn unit_memtrackers_format_size() {
+        assert_eq!(format_size(0), "0B");
+        assert_eq!(format_size(1023), "1023B");
+        assert_eq!(format_size(2048), "2.00K");
+        assert_eq!(format_size(45246054), "43.15M");
+        assert_eq!(format_size(1073741824), "1.00G");
+        assert_eq!(format_size(-2048), "-2.00K");
+    }
+
+    #[test]
+    fn unit_memtrackers_tree_and_top_consumers() {
+        let memtracker = |hostname_port: &str, id: &str, depth: &str, current_consumption: &str| MemTrackers {
+            hostname_port: hostname_port.to_string(),
+            id: id.to_string(),
+            current_consumption: current_consumption.to_string(),
+            peak_consumption: current_consumption.to_string(),
+            limit: "none".to_string(),
+            depth: depth.to_string(),
+            ..Default::default()
+        };
+        let allmemtrackers = AllMemTrackers { memtrackers: vec![
+            memtracker("yb-1.local:9000", "root", "0", "100.00M"),
+            memtracker("yb-1.local:9000", "server", "1", "60.00M"),
+            memtracker("yb-1.local:9000", "MemTable", "2", "50.00M"),
+            memtracker("yb-1.local:9000", "Call", "2", "5.00M"),
+            memtracker("yb-1.local:9000", "TCMalloc Central Cache", "1", "30.00M"),
+            memtracker("yb-2.local:9000", "root", "0", "40.00M"),
+            memtracker("yb-2.local:9000", "server", "1", "20.00M"),
+            memtracker("yb-2.local:9000", "MemTable", "2", "10.00M"),
+            memtracker("yb-3.local:9000", "root", "0", "40.00M"),
+            memtracker("yb-3.local:9000", "server", "1", "20.00M"),
+            memtracker("yb-3.local:9000", "MemTable", "2", "10.00M"),
+            memtracker("yb-3.local:9000", "Redis", "2", "512.0K"),
+        ]};
+        let tree = allmemtrackers.tree();
+        assert_eq!(tree[0].path, "root");
+        assert_eq!(tree[0].children_consumption, 90 * 1024 * 1024);
+        assert_eq!(tree[0].descendants, 4);
+        assert_eq!(tree[1].path, "root->server");
+        assert_eq!(tree[1].children_consumption, 55 * 1024 * 1024);
+        assert_eq!(tree[1].descendants, 2);
+        assert_eq!(tree[5].path, "root");
+        assert_eq!(tree[5].children_consumption, 20 * 1024 * 1024);
+        assert_eq!(tree[5].descendants, 2);
+
+        let top_consumers = allmemtrackers.top_consumers(&utility::set_regex(&None), &utility::set_regex(&Some("MemTable".to_string())), 2);
+        assert_eq!(top_consumers.len(), 2);
+        assert_eq!(top_consumers[0], MemTrackerConsumer {
+            hostname_port: "yb-1.local:9000".to_string(),
+            path: "root->server->MemTable".to_string(),
+            current_consumption: 50 * 1024 * 1024,
+            peers: 2,
+            peers_average: (10 * 1024 * 1024) as f64,
+        });
+        assert_eq!(top_consumers[0].peers_ratio(), Some(5.0));
+        assert_eq!(top_consumers[1].peers_average, (30 * 1024 * 1024) as f64);
+
+        // the memtrackers that the other hosts do not have are ranked first, by consumption,
+        // the 512K of Redis on yb-3 is below the minimum consumption.
+        let top_consumers = allmemtrackers.top_consumers(&utility::set_regex(&None), &utility::set_regex(&None), 3);
+        assert_eq!(top_consumers.iter().map(|consumer| consumer.path.as_str()).collect::<Vec<_>>(),
+                   vec!["root->TCMalloc Central Cache", "root->server->Call", "root->server->MemTable"]);
+        assert_eq!(top_consumers[0].peers_ratio(), None);
+    }
+
     #[test]
     fn unit_parse_memtrackers_data() {
         // This is what /mem-trackers returns.
@@ -1,6 +1,7 @@
 //! The structs
 //!
 use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
 
 /// The root struct for deserializing the memtrackers HTML table.
 ///
@@ -21,3 +22,51 @@ pub struct MemTrackers {
 pub struct AllMemTrackers {
     pub memtrackers: Vec<MemTrackers>,
 }
+/// A memtracker in the memtrackers tree of a host.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MemTrackerNode {
+    pub hostname_port: String,
+    pub timestamp: DateTime<Local>,
+    /// The path of the memtracker from the root, such as "root->server->BlockBasedTable".
+    pub path: String,
+    pub id: String,
+    pub depth: usize,
+    pub current_consumption: i64,
+    pub peak_consumption: i64,
+    pub limit: Option<i64>,
+    /// The sum of the current consumption of the direct children.
+    pub children_consumption: i64,
+    /// The number of memtrackers below this memtracker.
+    pub descendants: usize,
+}
+/// The consumption of a memtracker on a host, compared with the same memtracker path on the other hosts.
+#[derive(Debug, Default, PartialEq)]
+pub struct MemTrackerConsumer {
+    pub hostname_port: String,
+    pub path: String,
+    pub current_consumption: i64,
+    /// The number of other hosts with the same memtracker path.
+    pub peers: usize,
+    /// The average current consumption of the same memtracker path on the other hosts.
+    pub peers_average: f64,
+}
+// diff
+/// BTreeMap for storing a memtrackers diff struct per `hostname_port` and memtracker path.
+type BTreeMemTrackersDiff = BTreeMap<(String, String), MemTrackersDiffFields>;
+/// The wrapper struct for holding the btreemap holding the diff structs.
+#[derive(Debug, Default)]
+pub struct MemTrackersDiff {
+    pub btreememtrackersdiff: BTreeMemTrackersDiff,
+}
+/// The struct that holds the first and second snapshot consumption in bytes of a memtracker.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MemTrackersDiffFields {
+    pub first_snapshot_time: DateTime<Local>,
+    pub first_current_consumption: i64,
+    pub first_peak_consumption: i64,
+    pub second_snapshot_time: DateTime<Local>,
+    pub second_current_consumption: i64,
+    pub second_peak_consumption: i64,
+    /// The limit of the second snapshot, a memtracker without a limit shows "none".
+    pub limit: Option<i64>,
+}
@@ -4,6 +4,7 @@ use log::*;
 /// The struct that contains all the details for a named statistic.
 /// This struct is used in [CountSumStatistics.countsumstatisticdetails], which holds a HashMap with the statistic name as key and this struct as value.
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct CountSumStatisticDetails {
     pub unit: String,
     pub unit_suffix: String,
@@ -19,7 +20,7 @@ impl CountSumStatistics {
     /// Take a statistic name, and return the details about it.
     /// If it doesn't exist, it returns '?', and generates logging at the info level.
     pub fn lookup(&self, argument: &str) -> &CountSumStatisticDetails {
-        match self.countsumstatisticsdetails.get(&argument.to_string()) {
+        match self.countsumstatisticsdetails.get(argument) {
             Some(lookup) => lookup,
             None => {
                 info!("statistic not found! -> table.insert(\"{}\",\"?\",\"?\");", argument);
@@ -1,13 +1,15 @@
 //! The  impls and functions
 //!
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::BTreeMap, sync::mpsc::channel, time::Instant};
 use chrono::{DateTime, Local};
+use prometheus_parse::Value;
 use regex::Regex;
 use log::*;
+use colored::*;
 use anyhow::Result;
 use crate::{metrics, utility};
 use crate::snapshot;
-use crate::metrics::{Metrics::{MetricValue, MetricCountSum, MetricCountSumRows}, MetricEntity, AllMetricEntity, MetricEntityDiff, MetricDiffValues, Attributes, MetricDiffCountSum, MetricDiffCountSumRows};
+use crate::metrics::{Metrics::{MetricValue, MetricCountSum, MetricCountSumRows}, MetricEntity, AllMetricEntity, MetricEntityDiff, MetricDiffValues, Attributes, MetricDiffCountSum, MetricDiffCountSumRows, CdcDiff, CdcDiffValues, CdcStreamKey, RocksDbRatios, MetricsSource};
 use crate::Opts;
 
 impl AllMetricEntity {
@@ -19,12 +21,13 @@ impl AllMetricEntity {
         ports: &Vec<&str>,
         snapshot_number: i32,
         parallel: usize,
+        metrics_source: &MetricsSource,
     ) -> Result<()>
     {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel).await;
+        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel, metrics_source).await;
         snapshot::save_snapshot_json(snapshot_number, "metrics", allmetricentity.metricentity)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
@@ -34,6 +37,7 @@ impl AllMetricEntity {
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         parallel: usize,
+        metrics_source: &MetricsSource,
     ) -> AllMetricEntity
     {
         info!("begin parallel http read");
@@ -47,7 +51,7 @@ impl AllMetricEntity {
                     let tx = tx.clone();
                     s.spawn(move |_| {
                         let detail_snapshot_time = Local::now();
-                        let mut metrics = AllMetricEntity::read_http(host, port);
+                        let mut metrics = AllMetricEntity::read_http(host, port, metrics_source);
                         metrics.iter_mut().for_each(|r| r.hostname_port = Some(format!("{}:{}", host, port)));
                         metrics.iter_mut().for_each(|r| r.timestamp = Some(detail_snapshot_time));
                         tx.send(metrics).expect("error sending data via tx");
@@ -70,14 +74,46 @@ impl AllMetricEntity {
 
         allmetricentity
     }
+    /// Read the metrics of a server from the endpoint set with metrics_source:
+    /// - json: `/metrics`.
+    /// - prometheus: `/prometheus-metrics`.
+    /// - auto: `/metrics`, and `/prometheus-metrics` if `/metrics` did not return any metrics.
     pub fn read_http(
         host: &str,
         port: &str,
+        metrics_source: &MetricsSource,
+    ) -> Vec<MetricEntity>
+    {
+        match metrics_source {
+            MetricsSource::Json => AllMetricEntity::read_http_json(host, port),
+            MetricsSource::Prometheus => AllMetricEntity::read_http_prometheus(host, port),
+            MetricsSource::Auto => {
+                let metrics = AllMetricEntity::read_http_json(host, port);
+                if metrics.is_empty() {
+                    debug!("No metrics from {}:{}/metrics, trying /prometheus-metrics", host, port);
+                    AllMetricEntity::read_http_prometheus(host, port)
+                } else {
+                    metrics
+                }
+            },
+        }
+    }
+    fn read_http_json(
+        host: &str,
+        port: &str,
     ) -> Vec<MetricEntity>
     {
         let data_from_http = utility::http_get(host, port, "metrics");
         AllMetricEntity::parse_metrics(data_from_http, host, port)
     }
+    fn read_http_prometheus(
+        host: &str,
+        port: &str,
+    ) -> Vec<MetricEntity>
+    {
+        let data_from_http = utility::http_get(host, port, "prometheus-metrics");
+        AllMetricEntity::parse_prometheus_metrics(data_from_http, host, port)
+    }
     fn parse_metrics(
         http_data: String,
         host: &str,
@@ -90,6 +126,300 @@ impl AllMetricEntity {
                 Vec::<MetricEntity>::new()
             })
     }
+    /// Parse the prometheus format `/prometheus-metrics` output into [MetricEntity] entities.
+    ///
+    /// The prometheus output contains a line per metric, and uses labels to identify the entity:
+    /// ```text
+    /// rocksdb_number_db_seek{table_id="000033e8000030008000000000004000",table_name="t",namespace_name="yugabyte",metric_type="tablet",metric_id="5a1c4d3a37c8447fb1c4f1c0c2c7a8a0",exported_instance="yb-1.local:9000"} 12 1680000000000
+    /// handler_latency_yb_tserver_TabletServerService_Read_count{metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 25 1680000000000
+    /// handler_latency_yb_tserver_TabletServerService_Read_sum{metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 631456 1680000000000
+    /// ```
+    /// - The labels metric_type and metric_id are the type and id of the entity.
+    /// - The labels table_id, table_name, namespace_name and stream_id are the attributes.
+    /// - A metric that has a `_count` and a `_sum` row is a countsum, or a countsumrows if it has a `_rows` row too.
+    ///   The mean is calculated. The min, max and percentiles are taken from the rows of the metric with a quantile label
+    ///   (`quantile="p99"` or `quantile="0.99"`), and are set to zero if these are not exposed.
+    /// - A row with a quantile label of a metric that is not a countsum is a value named `<metric>_<quantile>`.
+    /// - Any other metric is a value.
+    fn parse_prometheus_metrics(
+        http_data: String,
+        host: &str,
+        port: &str,
+    ) -> Vec<MetricEntity>
+    {
+        let scrape = match prometheus_parse::Scrape::parse(http_data.lines().map(|s| Ok(s.to_owned()))) {
+            Ok(scrape) => scrape,
+            Err(e) => {
+                debug!("Could not parse {}:{}/prometheus-metrics, error: {}", host, port, e);
+                return Vec::new();
+            },
+        };
+        // (metric name, quantile) -> value
+        type Samples = BTreeMap<(String, Option<String>), f64>;
+        // (metric_type, metric_id) -> (attributes, samples)
+        let mut entities: BTreeMap<(String, String), (Attributes, Samples)> = BTreeMap::new();
+        for sample in scrape.samples
+        {
+            let (Some(metric_type), Some(metric_id)) = (sample.labels.get("metric_type"), sample.labels.get("metric_id")) else { continue };
+            let entity = entities.entry((metric_type.to_string(), metric_id.to_string()))
+                .or_insert_with(|| (Attributes {
+                    stream_id: sample.labels.get("stream_id").map(|label| label.to_string()),
+                    namespace_name: sample.labels.get("namespace_name").map(|label| label.to_string()),
+                    table_name: sample.labels.get("table_name").map(|label| label.to_string()),
+                    table_id: sample.labels.get("table_id").map(|label| label.to_string()),
+                }, BTreeMap::new()));
+            match sample.value {
+                Value::Counter(value) | Value::Gauge(value) | Value::Untyped(value) => {
+                    entity.1.insert((sample.metric, sample.labels.get("quantile").map(|quantile| quantile.to_string())), value);
+                },
+                Value::Summary(quantiles) => {
+                    for quantile in quantiles {
+                        entity.1.insert((sample.metric.clone(), Some(quantile.quantile.to_string())), quantile.count);
+                    }
+                },
+                Value::Histogram(_) => continue,
+            };
+        }
+
+        let mut metricentities = Vec::new();
+        for ((metrics_type, id), (attributes, values)) in entities
+        {
+            let mut metrics = Vec::new();
+            for ((name, quantile), value) in &values
+            {
+                // the name of the countsum if name is the row with the given suffix of a countsum.
+                let countsum_name = |suffix: &str| -> Option<&str> {
+                    let name = name.strip_suffix(suffix)?;
+                    (values.contains_key(&(format!("{}_count", name), None)) && values.contains_key(&(format!("{}_sum", name), None))).then_some(name)
+                };
+                if let Some(quantile) = quantile
+                {
+                    // the quantiles of a countsum are part of the countsum.
+                    if values.contains_key(&(format!("{}_count", name), None)) && values.contains_key(&(format!("{}_sum", name), None)) { continue };
+                    metrics.push(MetricValue { name: format!("{}_{}", name, quantile), value: *value as i64 });
+                }
+                else if let Some(countsum_name) = countsum_name("_count")
+                {
+                    let total_count = *value as u64;
+                    let total_sum = values[&(format!("{}_sum", countsum_name), None)] as u64;
+                    // the value of the first quantile label found for the countsum, or zero.
+                    let quantile = |labels: &[&str]| -> u64 {
+                        labels.iter()
+                            .find_map(|label| values.get(&(countsum_name.to_string(), Some(label.to_string()))))
+                            .map(|value| *value as u64)
+                            .unwrap_or_default()
+                    };
+                    match values.get(&(format!("{}_rows", countsum_name), None)) {
+                        Some(rows) => metrics.push(MetricCountSumRows {
+                            name: countsum_name.to_string(),
+                            count: total_count,
+                            sum: total_sum,
+                            rows: *rows as u64,
+                        }),
+                        None => metrics.push(MetricCountSum {
+                            name: countsum_name.to_string(),
+                            total_count,
+                            min: quantile(&["min", "0"]),
+                            mean: if total_count == 0 { 0.0 } else { total_sum as f64 / total_count as f64 },
+                            percentile_75: quantile(&["p75", "0.75"]),
+                            percentile_95: quantile(&["p95", "0.95"]),
+                            percentile_99: quantile(&["p99", "0.99"]),
+                            percentile_99_9: quantile(&["p99.9", "p999", "0.999"]),
+                            percentile_99_99: quantile(&["p99.99", "p9999", "0.9999"]),
+                            max: quantile(&["max", "1"]),
+                            total_sum,
+                        }),
+                    }
+                }
+                // the _sum and _rows rows are part of the countsum.
+                else if countsum_name("_sum").is_some() || countsum_name("_rows").is_some()
+                {
+                    continue;
+                }
+                else
+                {
+                    metrics.push(MetricValue { name: name.to_string(), value: *value as i64 });
+                }
+            }
+            metricentities.push(MetricEntity {
+                hostname_port: None,
+                timestamp: None,
+                metrics_type,
+                id,
+                attributes: Some(attributes),
+                metrics,
+            });
+        }
+        metricentities
+    }
+    /// Print the absolute values of the metrics.
+    ///
+    /// This works in the same way as [MetricEntityDiff::print] with regards to details:
+    /// if details_enable is not set, the table, tablet, cdc and cdcsdk entities are summed per server.
+    /// Values and countsums that are zero are not printed.
+    pub async fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        table_name_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        // hostname_port, metric_type, metric_id, metric_name
+        type MetricKey = (String, String, String, String);
+        // key -> (table_info, value)
+        let mut values: BTreeMap<MetricKey, (String, i64)> = BTreeMap::new();
+        // key -> (table_info, total_count, total_sum, max)
+        let mut countsums: BTreeMap<MetricKey, (String, u64, u64, u64)> = BTreeMap::new();
+        // key -> (count, sum, rows)
+        let mut countsumrows: BTreeMap<MetricKey, (u64, u64, u64)> = BTreeMap::new();
+
+        for metricentity in self.metricentity.iter()
+            .filter(|r| hostname_filter.is_match(r.hostname_port.as_ref().expect("hostname:port should be set")))
+        {
+            let table_name = metricentity.attributes
+                .as_ref()
+                .and_then(|attributes| attributes.table_name.clone())
+                .unwrap_or_default();
+            if !table_name_filter.is_match(&table_name)
+            {
+                continue;
+            }
+            let namespace = metricentity.attributes
+                .as_ref()
+                .and_then(|attributes| attributes.namespace_name.clone())
+                .unwrap_or_default();
+            let (metric_id, table_info) = if !*details_enable
+                && (metricentity.metrics_type == "table"
+                || metricentity.metrics_type == "tablet"
+                || metricentity.metrics_type == "cdc"
+                || metricentity.metrics_type == "cdcsdk")
+            {
+                ("-".to_string(), "".to_string())
+            }
+            else if namespace.is_empty()
+            {
+                (metricentity.id.clone(), table_name)
+            }
+            else
+            {
+                (metricentity.id.clone(), format!("{}.{}", namespace, table_name))
+            };
+            let hostname_port = metricentity.hostname_port.clone().expect("hostname:port should be set");
+
+            for metric in metricentity.metrics.iter()
+            {
+                match metric
+                {
+                    MetricValue { name, value } if stat_name_filter.is_match(name) =>
+                        {
+                            values.entry((hostname_port.clone(), metricentity.metrics_type.clone(), metric_id.clone(), name.to_string()))
+                                .and_modify(|row| row.1 += value)
+                                .or_insert((table_info.clone(), *value));
+                        },
+                    MetricCountSum { name, total_count, total_sum, max, .. } if stat_name_filter.is_match(name) =>
+                        {
+                            countsums.entry((hostname_port.clone(), metricentity.metrics_type.clone(), metric_id.clone(), name.to_string()))
+                                .and_modify(|row| {
+                                    row.1 += total_count;
+                                    row.2 += total_sum;
+                                    row.3 = row.3.max(*max);
+                                })
+                                .or_insert((table_info.clone(), *total_count, *total_sum, *max));
+                        },
+                    MetricCountSumRows { name, count, sum, rows } if stat_name_filter.is_match(name) =>
+                        {
+                            countsumrows.entry((hostname_port.clone(), metricentity.metrics_type.clone(), metric_id.clone(), name.to_string()))
+                                .and_modify(|row| {
+                                    row.0 += count;
+                                    row.1 += sum;
+                                    row.2 += rows;
+                                })
+                                .or_insert((*count, *sum, *rows));
+                        },
+                    _ => {},
+                }
+            }
+        }
+
+        let value_statistics = metrics::ValueStatistics::create();
+        for ((hostname, metric_type, metric_id, metric_name), (table_info, value)) in values.iter()
+            .filter(|(_, (_, value))| *value != 0)
+        {
+            let metadata = value_statistics.lookup(metric_name);
+            if *details_enable
+            {
+                println!("{:20} {:8} {:32} {:30} {:70} {:15} {:6} {}",
+                         hostname,
+                         metric_type,
+                         metric_id,
+                         table_info,
+                         metric_name,
+                         value,
+                         metadata.unit_suffix,
+                         metadata.stat_type,
+                );
+            }
+            else
+            {
+                println!("{:20} {:8} {:70} {:15} {:6} {}",
+                         hostname,
+                         metric_type,
+                         metric_name,
+                         value,
+                         metadata.unit_suffix,
+                         metadata.stat_type,
+                );
+            }
+        }
+        let countsum_statistics = metrics::CountSumStatistics::create();
+        for ((hostname, metric_type, metric_id, metric_name), (table_info, total_count, total_sum, max)) in countsums.iter()
+            .filter(|(_, (_, total_count, _, _))| *total_count != 0)
+        {
+            let metadata = countsum_statistics.lookup(metric_name);
+            if *details_enable
+            {
+                println!("{:20} {:8} {:32} {:30} {:70} {:15} avg: {:>15.3} max: {:>15} tot: {:>15} {:10}",
+                         hostname,
+                         metric_type,
+                         metric_id,
+                         table_info,
+                         metric_name,
+                         total_count,
+                         *total_sum as f64 / *total_count as f64,
+                         max,
+                         total_sum,
+                         metadata.unit_suffix
+                );
+            }
+            else
+            {
+                println!("{:20} {:8} {:70} {:15} avg: {:>15.3} max: {:>15} tot: {:>15} {:10}",
+                         hostname,
+                         metric_type,
+                         metric_name,
+                         total_count,
+                         *total_sum as f64 / *total_count as f64,
+                         max,
+                         total_sum,
+                         metadata.unit_suffix
+                );
+            }
+        }
+        for ((hostname, _metric_type, _metric_id, metric_name), (count, sum, rows)) in countsumrows.iter()
+            .filter(|(_, (count, _, _))| *count != 0)
+        {
+            println!("{:20} {:70} {:>15} avg: {:>15.3} tot: {:>15.3} ms, avg: {:>15} tot: {:>15} rows",
+                     hostname,
+                     metric_name,
+                     count,
+                     (*sum as f64 / 1000.0) / *count as f64,
+                     *sum as f64 / 1000.0,
+                     rows / count,
+                     rows
+            );
+        }
+    }
 }
 
 impl MetricEntityDiff {
@@ -471,6 +801,15 @@ impl MetricEntityDiff {
                 }
             }
         }
+        // a server that has been restarted has a lower uptime in the second snapshot.
+        self.restarted_servers = self.btreemetricdiffvalue
+            .iter()
+            .filter(|((_, metric_type, _, metric_name), row)| metric_type == "server"
+                && metric_name == "server_uptime_ms"
+                && row.second_value > 0
+                && row.second_value < row.first_value)
+            .map(|((hostname_port, _, _, _), _)| hostname_port.clone())
+            .collect();
     }
     pub async fn print(
         &self,
@@ -481,6 +820,7 @@ impl MetricEntityDiff {
         gauges_enable: &bool,
     )
     {
+        utility::print_restarts(&self.restarted_servers, hostname_filter);
         // value_diff
         let value_statistics = metrics::ValueStatistics::create();
         for ((hostname, metric_type, metric_id, metric_name), row) in &self.btreemetricdiffvalue
@@ -496,8 +836,9 @@ impl MetricEntityDiff {
             {
                 // show as counter.
                 // the choice of != gauge is deliberate here: if it's unknown, it'll be shown as counter.
+                let (difference, reset) = utility::counter_difference(row.first_value, row.second_value, self.restarted_servers.contains(hostname));
                 if metadata.stat_type != "gauge"
-                    && row.second_value - row.first_value != 0
+                    && difference != 0
                 {
                     if *details_enable
                     {
@@ -515,26 +856,28 @@ impl MetricEntityDiff {
                         };
 
                         //println!("{:20} {:8} {:32} {:15} {:30} {:70} {:15} {:6} {:>15.3} /s",
-                        println!("{:20} {:8} {:32} {:30} {:70} {:15} {:6} {:>15.3} /s",
+                        println!("{:20} {:8} {:32} {:30} {:70} {:15} {:6} {:>15.3} /s {}",
                                  hostname,
                                  metric_type,
                                  metric_id,
                                  table_info,
                                  metric_name,
-                                 row.second_value - row.first_value,
+                                 difference,
                                  metadata.unit_suffix,
-                                 ((row.second_value - row.first_value) as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64)
+                                 (difference as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64),
+                                 if reset { "reset".red() } else { "".normal() },
                         );
                     }
                     else
                     {
-                        println!("{:20} {:8} {:70} {:15} {:6} {:>15.3} /s",
+                        println!("{:20} {:8} {:70} {:15} {:6} {:>15.3} /s {}",
                                  hostname,
                                  metric_type,
                                  metric_name,
-                                 row.second_value - row.first_value,
+                                 difference,
                                  metadata.unit_suffix,
-                                 ((row.second_value - row.first_value) as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64)
+                                 (difference as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64),
+                                 if reset { "reset".red() } else { "".normal() },
                         );
                     }
                 }
@@ -596,9 +939,11 @@ impl MetricEntityDiff {
                 // if second_total_count = 0, the statistic was zero, meaning no use,
                 // or it wasn't filled out for the second snapshot, meaning the endpoint went away.
                 // in both cases do not report.
-                //if row.second_total_count - row.first_total_count != 0
-                // saturating_sub halts the value at 0 the first value happens to be higher.
-                if row.second_total_count.saturating_sub(row.first_total_count) != 0
+                // if the first value happens to be higher, the statistic has been reset.
+                let restarted = self.restarted_servers.contains(hostname) || row.second_total_count < row.first_total_count;
+                let (total_count, reset) = utility::counter_difference(row.first_total_count, row.second_total_count, restarted);
+                let (total_sum, _) = utility::counter_difference(row.first_total_sum, row.second_total_sum, restarted);
+                if total_count != 0
                 {
                     if *details_enable
                     {
@@ -614,30 +959,32 @@ impl MetricEntityDiff {
                         {
                             format!("{}.{}", row.namespace, row.table_name)
                         };
-                        println!("{:20} {:8} {:32} {:30} {:70} {:15}        {:>15.3} /s avg: {:9.0} tot: {:>15.3} {:10}",
+                        println!("{:20} {:8} {:32} {:30} {:70} {:15}        {:>15.3} /s avg: {:9.0} tot: {:>15.3} {:10} {}",
                                  hostname,
                                  metric_type,
                                  metric_id,
                                  table_info,
                                  metric_name,
-                                 row.second_total_count - row.first_total_count,
-                                 (row.second_total_count - row.first_total_count) as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64,
-                                 ((row.second_total_sum - row.first_total_sum) / (row.second_total_count - row.first_total_count)) as f64,
-                                 row.second_total_sum - row.first_total_sum,
-                                 metadata.unit_suffix
+                                 total_count,
+                                 total_count as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64,
+                                 (total_sum / total_count) as f64,
+                                 total_sum,
+                                 metadata.unit_suffix,
+                                 if reset { "reset".red() } else { "".normal() },
                         );
                     }
                     else
                     {
-                        println!("{:20} {:8} {:70} {:15}        {:>15.3} /s avg: {:9.0} tot: {:>15.3} {:10}",
+                        println!("{:20} {:8} {:70} {:15}        {:>15.3} /s avg: {:9.0} tot: {:>15.3} {:10} {}",
                                  hostname,
                                  metric_type,
                                  metric_name,
-                                 row.second_total_count - row.first_total_count,
-                                 (row.second_total_count - row.first_total_count) as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64,
-                                 ((row.second_total_sum - row.first_total_sum) / (row.second_total_count - row.first_total_count)) as f64,
-                                 row.second_total_sum - row.first_total_sum,
-                                 metadata.unit_suffix
+                                 total_count,
+                                 total_count as f64 / (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 * 1000_f64,
+                                 (total_sum / total_count) as f64,
+                                 total_sum,
+                                 metadata.unit_suffix,
+                                 if reset { "reset".red() } else { "".normal() },
                         );
                     }
                 }
@@ -646,31 +993,161 @@ impl MetricEntityDiff {
         // countsumrows_diff
         for ((hostname, _metric_type, _metric_id, metric_name), row) in &self.btreemetricdiffcountsumrows
         {
+            let restarted = self.restarted_servers.contains(hostname) || row.second_count < row.first_count;
+            let (count, reset) = utility::counter_difference(row.first_count, row.second_count, restarted);
+            let (sum, _) = utility::counter_difference(row.first_sum, row.second_sum, restarted);
+            let (rows, _) = utility::counter_difference(row.first_rows, row.second_rows, restarted);
             if hostname_filter.is_match(hostname)
                 && stat_name_filter.is_match(metric_name)
-                && row.second_count - row.first_count != 0
+                && table_name_filter.is_match(&row.table_name)
+                // the statistic wasn't filled out for the second snapshot, meaning the endpoint went away.
+                && row.second_count != 0
+                && count != 0
             {
-                println!("{:20} {:70} {:>15} avg: {:>15.3} tot: {:>15.3} ms, avg: {:>15} tot: {:>15} rows",
+                println!("{:20} {:70} {:>15} avg: {:>15.3} tot: {:>15.3} ms, avg: {:>15} tot: {:>15} rows {}",
                          hostname,
                          metric_name,
-                         row.second_count - row.first_count,
-                         ((row.second_sum as f64 - row.first_sum as f64) / 1000.0) / (row.second_count - row.first_count) as f64,
-                         (row.second_sum as f64 - row.first_sum as f64) / 1000.0,
-                         (row.second_rows - row.first_rows) / (row.second_count - row.first_count),
-                         row.second_rows - row.first_rows
+                         count,
+                         (sum as f64 / 1000.0) / count as f64,
+                         sum as f64 / 1000.0,
+                         rows / count,
+                         rows,
+                         if reset { "reset".red() } else { "".normal() },
                 );
             }
         }
     }
+    /// Calculate ratios derived from the rocksdb statistics, to understand the efficiency of DocDB storage.
+    ///
+    /// If details_enable is not set, the ratios are calculated per server.
+    /// If details_enable is set, the ratios are calculated per server and table, and the table_name_filter is applied.
+    ///
+    /// - cache hit: rocksdb_block_cache_hit / (rocksdb_block_cache_hit + rocksdb_block_cache_miss)
+    /// - bloom useful: rocksdb_bloom_filter_useful / rocksdb_bloom_filter_checked,
+    ///   the fraction of bloom filter checks that prevented reading an SST file.
+    /// - write amp: rocksdb_compact_write_bytes / rocksdb_flush_write_bytes,
+    ///   the number of bytes written by compactions for every byte flushed from the memtables.
+    /// - read amp: (rocksdb_block_cache_data_hit + rocksdb_block_cache_data_miss) / (rocksdb_number_db_seek + rocksdb_number_db_next),
+    ///   the number of data blocks accessed per seek or next.
+    /// - stall: rocksdb_stall_micros, the time writes were stalled.
+    ///
+    /// Servers or tables without any rocksdb activity are not returned.
+    fn rocksdb_ratios(
+        &self,
+        hostname_filter: &Regex,
+        table_name_filter: &Regex,
+        details_enable: &bool,
+    ) -> BTreeMap<(String, String), RocksDbRatios>
+    {
+        // (hostname_port, table) -> metric_name -> difference
+        let mut servers: BTreeMap<(String, String), BTreeMap<String, i64>> = BTreeMap::new();
+        let mut unavailable: BTreeMap<(String, String), bool> = BTreeMap::new();
+        for ((hostname, metric_type, _metric_id, metric_name), row) in &self.btreemetricdiffvalue
+        {
+            if !metric_name.starts_with("rocksdb_")
+                || !(metric_type == "tablet" || metric_type == "table")
+                || !hostname_filter.is_match(hostname)
+                || (*details_enable && !table_name_filter.is_match(&row.table_name))
+            {
+                continue;
+            }
+            let table = if !*details_enable
+            {
+                "-".to_string()
+            }
+            else if row.namespace.is_empty()
+            {
+                row.table_name.clone()
+            }
+            else
+            {
+                format!("{}.{}", row.namespace, row.table_name)
+            };
+            // the statistic wasn't filled out for the second snapshot.
+            if row.second_value == 0
+            {
+                if row.first_value != 0
+                {
+                    unavailable.insert((hostname.to_string(), table), true);
+                }
+                continue;
+            }
+            *servers.entry((hostname.to_string(), table))
+                .or_default()
+                .entry(metric_name.to_string())
+                .or_default() += utility::counter_difference(row.first_value, row.second_value, self.restarted_servers.contains(hostname)).0;
+        }
+
+        let ratio = |numerator: i64, denominator: i64| -> Option<f64> {
+            if denominator == 0 { None } else { Some(numerator as f64 / denominator as f64) }
+        };
+
+        let mut ratios: BTreeMap<(String, String), RocksDbRatios> = unavailable
+            .into_keys()
+            .map(|key| (key, RocksDbRatios { unavailable: true, ..Default::default() }))
+            .collect();
+        for (key, statistics) in servers
+        {
+            // no activity at all.
+            if statistics.values().all(|value| *value == 0) || ratios.contains_key(&key)
+            {
+                continue;
+            }
+            let value = |name: &str| -> i64 { *statistics.get(name).unwrap_or(&0) };
+            ratios.insert(key, RocksDbRatios {
+                cache_hit_percentage: ratio(value("rocksdb_block_cache_hit"), value("rocksdb_block_cache_hit") + value("rocksdb_block_cache_miss")).map(|ratio| ratio * 100_f64),
+                bloom_useful_percentage: ratio(value("rocksdb_bloom_filter_useful"), value("rocksdb_bloom_filter_checked")).map(|ratio| ratio * 100_f64),
+                write_amplification: ratio(value("rocksdb_compact_write_bytes"), value("rocksdb_flush_write_bytes")),
+                read_amplification: ratio(value("rocksdb_block_cache_data_hit") + value("rocksdb_block_cache_data_miss"), value("rocksdb_number_db_seek") + value("rocksdb_number_db_next")),
+                stall_milliseconds: value("rocksdb_stall_micros") as f64 / 1000_f64,
+                unavailable: false,
+            });
+        }
+        ratios
+    }
+    /// Print the ratios derived from the rocksdb statistics, see [MetricEntityDiff::rocksdb_ratios].
+    ///
+    /// A ratio that cannot be calculated because the denominator is zero is shown as '-'.
+    /// If a statistic was present in the first snapshot but not in the second, the ratios are shown as 'n/a'.
+    pub fn print_rocksdb(
+        &self,
+        hostname_filter: &Regex,
+        table_name_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        utility::print_restarts(&self.restarted_servers, hostname_filter);
+        println!("{:20} {:40} {:>10} {:>13} {:>10} {:>10} {:>15}", "hostname_port", "table", "cache hit", "bloom useful", "write amp", "read amp", "stall ms");
+        for ((hostname, table), ratios) in self.rocksdb_ratios(hostname_filter, table_name_filter, details_enable)
+        {
+            if ratios.unavailable
+            {
+                println!("{:20} {:40} {:>10} {:>13} {:>10} {:>10} {:>15}", hostname, table, "n/a", "n/a", "n/a", "n/a", "n/a");
+                continue;
+            }
+            let ratio = |ratio: Option<f64>| ratio.map(|ratio| format!("{:.2}", ratio)).unwrap_or_else(|| "-".to_string());
+            let percentage = |ratio: Option<f64>| ratio.map(|ratio| format!("{:.2}%", ratio)).unwrap_or_else(|| "-".to_string());
+            println!("{:20} {:40} {:>10} {:>13} {:>10} {:>10} {:>15.3}",
+                     hostname,
+                     table,
+                     percentage(ratios.cache_hit_percentage),
+                     percentage(ratios.bloom_useful_percentage),
+                     ratio(ratios.write_amplification),
+                     ratio(ratios.read_amplification),
+                     ratios.stall_milliseconds,
+            );
+        }
+    }
     pub async fn adhoc_read_first_snapshot(
         &mut self,
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         parallel: usize,
         details_enable: bool,
+        metrics_source: &MetricsSource,
     )
     {
-        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel).await;
+        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel, metrics_source).await;
         self.first_snapshot(allmetricentity, &details_enable);
     }
     pub async fn adhoc_read_second_snapshot(
@@ -680,13 +1157,226 @@ impl MetricEntityDiff {
         parallel: usize,
         first_snapshot_time: &DateTime<Local>,
         details_enable: bool,
+        metrics_source: &MetricsSource,
     )
     {
-        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel).await;
+        let allmetricentity = AllMetricEntity::read_metrics(hosts, ports, parallel, metrics_source).await;
         self.second_snapshot(allmetricentity, &details_enable, first_snapshot_time);
     }
 }
 
+impl CdcDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+        begin_snapshot_time: &DateTime<Local>,
+        hostname_filter: &Regex,
+    ) -> Result<CdcDiff>
+    {
+        let mut cdcdiff = CdcDiff::new();
+
+        let mut allmetricentity = AllMetricEntity::new();
+        allmetricentity.metricentity = snapshot::read_snapshot_json(begin_snapshot, "metrics")?;
+        cdcdiff.first_snapshot(allmetricentity, hostname_filter);
+
+        let mut allmetricentity = AllMetricEntity::new();
+        allmetricentity.metricentity = snapshot::read_snapshot_json(end_snapshot, "metrics")?;
+        cdcdiff.second_snapshot(allmetricentity, hostname_filter, begin_snapshot_time);
+
+        Ok(cdcdiff)
+    }
+    /// Lag statistics are gauges: these are combined by taking the highest value, not the sum.
+    fn is_lag(
+        metric_name: &str,
+    ) -> bool
+    {
+        metric_name.contains("_lag_")
+    }
+    /// Group the cdc and cdcsdk metric entities by stream_id, type, table and metric name.
+    /// This combines the values of all tablets of a table on all servers.
+    fn group_by_stream(
+        allmetricentity: AllMetricEntity,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<CdcStreamKey, (DateTime<Local>, i64)>
+    {
+        let mut streams: BTreeMap<CdcStreamKey, (DateTime<Local>, i64)> = BTreeMap::new();
+        for metricentity in allmetricentity.metricentity
+            .into_iter()
+            .filter(|r| r.metrics_type == "cdc" || r.metrics_type == "cdcsdk")
+            .filter(|r| hostname_filter.is_match(r.hostname_port.as_ref().expect("hostname:port should be set")))
+        {
+            let attributes = metricentity.attributes.unwrap_or_default();
+            let stream_id = attributes.stream_id.unwrap_or_else(|| "-".to_string());
+            let table = match (attributes.namespace_name, attributes.table_name)
+            {
+                (Some(namespace), Some(table_name)) => format!("{}.{}", namespace, table_name),
+                (None, Some(table_name)) => table_name,
+                _ => "-".to_string(),
+            };
+            let timestamp = metricentity.timestamp.expect("timestamp should be set");
+            for metric in metricentity.metrics
+            {
+                let (name, value) = match metric
+                {
+                    MetricValue { name, value } => (name, value),
+                    MetricCountSum { name, total_sum, .. } => (name, total_sum as i64),
+                    _ => continue,
+                };
+                let is_lag = CdcDiff::is_lag(&name);
+                streams.entry((stream_id.clone(), metricentity.metrics_type.clone(), table.clone(), name))
+                    .and_modify(|(stored_timestamp, stored_value)| {
+                        *stored_timestamp = timestamp.max(*stored_timestamp);
+                        if is_lag
+                        {
+                            *stored_value = value.max(*stored_value);
+                        }
+                        else
+                        {
+                            *stored_value += value;
+                        }
+                    })
+                    .or_insert((timestamp, value));
+            }
+        }
+        streams
+    }
+    fn first_snapshot(
+        &mut self,
+        allmetricentity: AllMetricEntity,
+        hostname_filter: &Regex,
+    )
+    {
+        for (key, (timestamp, value)) in CdcDiff::group_by_stream(allmetricentity, hostname_filter)
+        {
+            self.btreecdcdiff.insert(key, CdcDiffValues {
+                first_snapshot_time: timestamp,
+                first_value: value,
+                ..Default::default()
+            });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allmetricentity: AllMetricEntity,
+        hostname_filter: &Regex,
+        first_snapshot_time: &DateTime<Local>,
+    )
+    {
+        for (key, (timestamp, value)) in CdcDiff::group_by_stream(allmetricentity, hostname_filter)
+        {
+            self.btreecdcdiff.entry(key)
+                .and_modify(|cdcdiffvalues| {
+                    cdcdiffvalues.second_snapshot_time = timestamp;
+                    cdcdiffvalues.second_value = value;
+                })
+                .or_insert(CdcDiffValues {
+                    first_snapshot_time: *first_snapshot_time,
+                    first_value: 0,
+                    second_snapshot_time: timestamp,
+                    second_value: value,
+                });
+        }
+    }
+    /// Print the lag and the throughput per stream and table.
+    ///
+    /// Lag is shown as the second (absolute) value and the difference with the first snapshot.
+    /// If the lag has grown between the snapshots, it is shown in red.
+    /// Counters are shown as the difference and the difference per second, if the difference is not zero.
+    pub fn print(
+        &self,
+        stat_name_filter: &Regex,
+        table_name_filter: &Regex,
+    )
+    {
+        let value_statistics = metrics::ValueStatistics::create();
+        for ((stream_id, metric_type, table, metric_name), row) in &self.btreecdcdiff
+        {
+            if !stat_name_filter.is_match(metric_name)
+                || !table_name_filter.is_match(table)
+                // the stream went away in the second snapshot.
+                || row.second_snapshot_time == DateTime::<Local>::default()
+            {
+                continue;
+            }
+            if CdcDiff::is_lag(metric_name)
+            {
+                let lag_difference = row.second_value - row.first_value;
+                let lag = format!("{:15} us {:+15}", row.second_value, lag_difference);
+                println!("{:32} {:6} {:40} {:50} {}",
+                         stream_id,
+                         metric_type,
+                         table,
+                         metric_name,
+                         if lag_difference > 0 { lag.red() } else { lag.normal() },
+                );
+            }
+            else
+            {
+                let (difference, reset) = utility::counter_difference(row.first_value, row.second_value, false);
+                if difference == 0
+                {
+                    continue;
+                }
+                let metadata = value_statistics.lookup(metric_name);
+                // snapshots with the same timestamp have no time between them to calculate a rate.
+                let milliseconds = (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds();
+                println!("{:32} {:6} {:40} {:50} {:15} {:6} {:>15.3} /s {}",
+                         stream_id,
+                         metric_type,
+                         table,
+                         metric_name,
+                         difference,
+                         metadata.unit_suffix,
+                         if milliseconds == 0 { 0_f64 } else { difference as f64 / milliseconds as f64 * 1000_f64 },
+                         if reset { "reset".red() } else { "".normal() },
+                );
+            }
+        }
+    }
+}
+
+pub async fn rocksdb_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let metricsdiff = MetricEntityDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp, &options.details_enable)?;
+    metricsdiff.print_rocksdb(&hostname_filter, &table_name_filter, &options.details_enable);
+
+    Ok(())
+}
+
+pub async fn cdc_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let cdcdiff = CdcDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp, &hostname_filter)?;
+    cdcdiff.print(&stat_name_filter, &table_name_filter);
+
+    Ok(())
+}
+
 pub async fn metrics_diff(
     options: &Opts,
 ) -> Result<()>
@@ -708,11 +1398,98 @@ pub async fn metrics_diff(
     Ok(())
 }
 
+pub async fn print_metrics(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+    match options.print_metrics.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allmetricentity = AllMetricEntity::new();
+            allmetricentity.metricentity = snapshot::read_snapshot_json(snapshot_number, "metrics")?;
+            allmetricentity.print(&hostname_filter, &stat_name_filter, &table_name_filter, &options.details_enable).await;
+        }
+        None => {
+            let allmetricentity = AllMetricEntity::read_metrics(&hosts, &ports, parallel, &options.metrics_source).await;
+            allmetricentity.print(&hostname_filter, &stat_name_filter, &table_name_filter, &options.details_enable).await;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::metrics::Metrics::{RejectedBooleanMetricValue, RejectedU64MetricValue};
     use super::*;
 
+    #[test]
+    fn unit_parse_prometheus_metrics() {
+        let prometheus_data = r#"
+rocksdb_number_db_seek{table_id="000033e8000030008000000000004000",table_name="t",namespace_name="yugabyte",metric_type="tablet",metric_id="5a1c4d3a37c8447fb1c4f1c0c2c7a8a0",exported_instance="yb-1.local:9000"} 12 1680000000000
+handler_latency_yb_tserver_TabletServerService_Read_count{metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 25 1680000000000
+handler_latency_yb_tserver_TabletServerService_Read_sum{metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 500 1680000000000
+handler_latency_yb_tserver_TabletServerService_Read{quantile="p95",metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 40 1680000000000
+handler_latency_yb_tserver_TabletServerService_Read{quantile="p99",metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 90 1680000000000
+handler_latency_yb_tserver_TabletServerService_Read{quantile="max",metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 120 1680000000000
+rpc_queue_time{quantile="p99",metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 7 1680000000000
+rpc_queue_time{quantile="p50",metric_type="server",metric_id="yb.tabletserver",exported_instance="yb-1.local:9000"} 3 1680000000000
+handler_latency_yb_ysqlserver_SQLProcessor_SelectStmt_count{metric_type="server",metric_id="yb.ysqlserver",exported_instance="yb-1.local:13000"} 25 1680000000000
+handler_latency_yb_ysqlserver_SQLProcessor_SelectStmt_sum{metric_type="server",metric_id="yb.ysqlserver",exported_instance="yb-1.local:13000"} 631456 1680000000000
+handler_latency_yb_ysqlserver_SQLProcessor_SelectStmt_rows{metric_type="server",metric_id="yb.ysqlserver",exported_instance="yb-1.local:13000"} 26 1680000000000
+no_entity_labels 1
+        "#.to_string();
+        let result = AllMetricEntity::parse_prometheus_metrics(prometheus_data, "", "");
+        assert_eq!(result.len(), 3);
+        let tablet = result.iter().find(|r| r.metrics_type == "tablet").unwrap();
+        assert_eq!(tablet.id, "5a1c4d3a37c8447fb1c4f1c0c2c7a8a0");
+        assert_eq!(tablet.attributes.as_ref().unwrap().table_name, Some("t".to_string()));
+        match &tablet.metrics[0] {
+            MetricValue { name, value } => {
+                assert_eq!(name, "rocksdb_number_db_seek");
+                assert_eq!(*value, 12);
+            },
+            _ => panic!("value expected"),
+        }
+        let tserver = result.iter().find(|r| r.id == "yb.tabletserver").unwrap();
+        // the countsum, and the quantiles of rpc_queue_time, which is not a countsum, as values.
+        assert_eq!(tserver.metrics.len(), 3);
+        match &tserver.metrics[0] {
+            MetricCountSum { name, total_count, total_sum, mean, percentile_95, percentile_99, max, min, .. } => {
+                assert_eq!(name, "handler_latency_yb_tserver_TabletServerService_Read");
+                assert_eq!(*total_count, 25);
+                assert_eq!(*total_sum, 500);
+                assert_eq!(*mean, 20.0);
+                // every quantile is kept, and doesn't overwrite the other quantiles.
+                assert_eq!(*percentile_95, 40);
+                assert_eq!(*percentile_99, 90);
+                assert_eq!(*max, 120);
+                assert_eq!(*min, 0);
+            },
+            _ => panic!("countsum expected"),
+        }
+        let values: Vec<(&String, &i64)> = tserver.metrics.iter()
+            .filter_map(|metric| match metric { MetricValue { name, value } => Some((name, value)), _ => None })
+            .collect();
+        assert_eq!(values, vec![(&"rpc_queue_time_p50".to_string(), &3), (&"rpc_queue_time_p99".to_string(), &7)]);
+        let ysql = result.iter().find(|r| r.id == "yb.ysqlserver").unwrap();
+        assert_eq!(ysql.metrics.len(), 1);
+        match &ysql.metrics[0] {
+            MetricCountSumRows { name, count, sum, rows } => {
+                assert_eq!(name, "handler_latency_yb_ysqlserver_SQLProcessor_SelectStmt");
+                assert_eq!(*count, 25);
+                assert_eq!(*sum, 631456);
+                assert_eq!(*rows, 26);
+            },
+            _ => panic!("countsumrows expected"),
+        }
+    }
+
     #[test]
     /// cdcsdk (change data capture software development kit) metrics value
     /// Please mind type cdc has an extra, unique, attribute: stream_id. This is currently not parsed.
@@ -745,6 +1522,154 @@ mod tests {
         };
     }
 
+    #[test]
+    /// A server that restarted between the snapshots has a lower server_uptime_ms in the second snapshot.
+    fn unit_metrics_diff_detect_restart() {
+        let first_json = r#"
+[
+    {
+        "type": "server",
+        "id": "yb.tabletserver",
+        "attributes": {},
+        "metrics": [
+            {
+                "name": "server_uptime_ms",
+                "value": 3600000
+            },
+            {
+                "name": "rpc_inbound_calls_created",
+                "value": 5000
+            }
+        ]
+    }
+]"#.to_string();
+        let second_json = first_json.replace("3600000", "60000").replace("5000", "100");
+        let mut metricentitydiff = MetricEntityDiff::new();
+        let mut allmetricentity = AllMetricEntity::new();
+        allmetricentity.metricentity = AllMetricEntity::parse_metrics(first_json, "", "");
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.hostname_port = Some("yb-1.local:9000".to_string()));
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.timestamp = Some(Local::now()));
+        metricentitydiff.first_snapshot(allmetricentity, &false);
+        let mut allmetricentity = AllMetricEntity::new();
+        allmetricentity.metricentity = AllMetricEntity::parse_metrics(second_json, "", "");
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.hostname_port = Some("yb-1.local:9000".to_string()));
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.timestamp = Some(Local::now()));
+        metricentitydiff.second_snapshot(allmetricentity, &false, &Local::now());
+        assert!(metricentitydiff.restarted_servers.contains("yb-1.local:9000"));
+        let row = metricentitydiff.btreemetricdiffvalue.get(&("yb-1.local:9000".to_string(), "server".to_string(), "yb.tabletserver".to_string(), "rpc_inbound_calls_created".to_string())).unwrap();
+        assert_eq!(utility::counter_difference(row.first_value, row.second_value, true), (100, true));
+    }
+
+    #[test]
+    /// rocksdb statistics of a tablet on two servers, where the second server lost the tablet between the snapshots.
+    fn unit_metrics_diff_rocksdb_ratios() {
+        let json = |cache_hit: i64, cache_miss: i64, flush_write_bytes: i64, compact_write_bytes: i64, stall_micros: i64| format!(r#"
+[
+    {{
+        "type": "tablet",
+        "id": "16add7b1248a45d2880e5527b2059b54",
+        "attributes": {{
+            "namespace_name": "yugabyte",
+            "table_name": "t",
+            "table_id": "000033e8000030008000000000004000"
+        }},
+        "metrics": [
+            {{ "name": "rocksdb_block_cache_hit", "value": {} }},
+            {{ "name": "rocksdb_block_cache_miss", "value": {} }},
+            {{ "name": "rocksdb_flush_write_bytes", "value": {} }},
+            {{ "name": "rocksdb_compact_write_bytes", "value": {} }},
+            {{ "name": "rocksdb_stall_micros", "value": {} }}
+        ]
+    }}
+]"#, cache_hit, cache_miss, flush_write_bytes, compact_write_bytes, stall_micros);
+        let first_json = json(100, 100, 1000, 0, 0);
+        let second_json = json(190, 110, 3000, 6000, 1500);
+        let snapshot = |hostname_port: &str, json: &str| {
+            let mut allmetricentity = AllMetricEntity::new();
+            allmetricentity.metricentity = AllMetricEntity::parse_metrics(json.to_string(), "", "");
+            allmetricentity.metricentity.iter_mut().for_each(|r| r.hostname_port = Some(hostname_port.to_string()));
+            allmetricentity.metricentity.iter_mut().for_each(|r| r.timestamp = Some(Local::now()));
+            allmetricentity
+        };
+        let mut first = snapshot("yb-1.local:9000", &first_json);
+        first.metricentity.extend(snapshot("yb-2.local:9000", &first_json).metricentity);
+        let mut metricentitydiff = MetricEntityDiff::new();
+        metricentitydiff.first_snapshot(first, &true);
+        metricentitydiff.second_snapshot(snapshot("yb-1.local:9000", &second_json), &true, &Local::now());
+
+        let ratios = metricentitydiff.rocksdb_ratios(&utility::set_regex(&None), &utility::set_regex(&None), &true);
+        assert_eq!(ratios[&("yb-1.local:9000".to_string(), "yugabyte.t".to_string())], RocksDbRatios {
+            // (190-100) hits / ((190-100) + (110-100)) lookups
+            cache_hit_percentage: Some(90.0),
+            bloom_useful_percentage: None,
+            // 6000 compaction bytes / 2000 flushed bytes
+            write_amplification: Some(3.0),
+            read_amplification: None,
+            stall_milliseconds: 1.5,
+            unavailable: false,
+        });
+        assert!(ratios[&("yb-2.local:9000".to_string(), "yugabyte.t".to_string())].unavailable);
+    }
+
+    #[test]
+    /// cdc metrics of two tablets of the same stream and table on two servers.
+    /// Lag is combined by taking the highest value, counters are summed.
+    fn unit_cdc_group_by_stream() {
+        let json = r#"
+[
+    {
+        "type": "cdc",
+        "id": ":face4edb05934e77b564857878cf5015:4457a26b28a64393ac626504aba5f571",
+        "attributes": {
+            "stream_id": "face4edb05934e77b564857878cf5015",
+            "table_name": "table0",
+            "namespace_name": "test",
+            "table_id": "c70ffbbe28f14e84b0559c405ae20197"
+        },
+        "metrics": [
+            {
+                "name": "async_replication_sent_lag_micros",
+                "value": 1000
+            },
+            {
+                "name": "records_sent",
+                "value": 10
+            }
+        ]
+    },
+    {
+        "type": "cdc",
+        "id": ":face4edb05934e77b564857878cf5015:8e5ea1d5a3cb4e9d8e4f6a8e1b1f1c1e",
+        "attributes": {
+            "stream_id": "face4edb05934e77b564857878cf5015",
+            "table_name": "table0",
+            "namespace_name": "test",
+            "table_id": "c70ffbbe28f14e84b0559c405ae20197"
+        },
+        "metrics": [
+            {
+                "name": "async_replication_sent_lag_micros",
+                "value": 3000
+            },
+            {
+                "name": "records_sent",
+                "value": 20
+            }
+        ]
+    }
+]"#.to_string();
+        let mut allmetricentity = AllMetricEntity::new();
+        allmetricentity.metricentity = AllMetricEntity::parse_metrics(json, "", "");
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.hostname_port = Some("yb-1.local:9000".to_string()));
+        allmetricentity.metricentity.iter_mut().for_each(|r| r.timestamp = Some(Local::now()));
+        let streams = CdcDiff::group_by_stream(allmetricentity, &Regex::new(".*").unwrap());
+        assert_eq!(streams.len(), 2);
+        let (_, lag) = streams.get(&("face4edb05934e77b564857878cf5015".to_string(), "cdc".to_string(), "test.table0".to_string(), "async_replication_sent_lag_micros".to_string())).unwrap();
+        assert_eq!(*lag, 3000);
+        let (_, records_sent) = streams.get(&("face4edb05934e77b564857878cf5015".to_string(), "cdc".to_string(), "test.table0".to_string(), "records_sent".to_string())).unwrap();
+        assert_eq!(*records_sent, 30);
+    }
+
     #[test]
     /// cdc (change data capture) metrics value
     /// Please mind type cdc has an extra, unique, attribute: stream_id.
@@ -1104,7 +2029,7 @@ mod tests {
         {
             let hostname = utility::get_hostname_master();
             let port = utility::get_port_master();
-            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1).await;
+            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1, &MetricsSource::Json).await;
             // a master will produce metrics.
             assert!(!allmetricentity.metricentity.is_empty());
         }
@@ -1112,7 +2037,7 @@ mod tests {
         async fn integration_parse_metrics_tserver() {
             let hostname = utility::get_hostname_tserver();
             let port = utility::get_port_tserver();
-            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1).await;
+            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1, &MetricsSource::Json).await;
             // a tablet server will produce metrics.
             assert!(!allmetricentity.metricentity.is_empty());
         }
@@ -1120,7 +2045,7 @@ mod tests {
         async fn integration_parse_metrics_ysql() {
             let hostname = utility::get_hostname_ysql();
             let port = utility::get_port_ysql();
-            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1).await;
+            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1, &MetricsSource::Json).await;
             // YSQL will produce metrics.
             assert!(!allmetricentity.metricentity.is_empty());
         }
@@ -1128,7 +2053,7 @@ mod tests {
         async fn integration_parse_metrics_ycql() {
             let hostname = utility::get_hostname_ycql();
             let port = utility::get_port_ycql();
-            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1).await;
+            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1, &MetricsSource::Json).await;
             // YCQL will produce metrics.
             assert!(!allmetricentity.metricentity.is_empty());
         }
@@ -1136,7 +2061,7 @@ mod tests {
         async fn integration_parse_metrics_yedis() {
             let hostname = utility::get_hostname_yedis();
             let port = utility::get_port_yedis();
-            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1).await;
+            let allmetricentity = AllMetricEntity::read_metrics(&vec![&hostname], &vec![&port], 1, &MetricsSource::Json).await;
             // YEDIS will produce metrics.
             assert!(!allmetricentity.metricentity.is_empty());
         }
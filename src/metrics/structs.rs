@@ -1,15 +1,15 @@
 //! The structs
 //!
 use chrono::{DateTime, Local};
-use std::collections::{BTreeMap};
+use std::collections::{BTreeMap, BTreeSet};
+use clap::ValueEnum;
 /// The root struct for deserializing `/metrics`.
 ///
-/// Struct to represent the metric entities found in the YugabyteDB
+/// Struct to represent the metric entities found in the YugabyteDB metrics endpoints of:
 /// - master
 /// - tablet server
 /// - YSQL
 /// - YCQL
-/// metrics endpoints.
 ///
 /// Mind the '[' at the top: this is a list of MetricEntity's.
 /// Therefore, parsing this endpoint will result in a vector of structs.
@@ -81,7 +81,7 @@ pub struct MetricEntity {
 ///                 "table_id": "c70ffbbe28f14e84b0559c405ae20197"
 ///             }
 /// ```
-/// For now, stream_id is only collected, not printed.
+/// The stream_id is used to group the cdc and cdcsdk entities in [CdcDiff].
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Attributes {
     pub stream_id: Option<String>,
@@ -242,7 +242,9 @@ type BTreeMetricDiffCountSumRows = BTreeMap<(String, String, String, String), Me
 #[derive(Debug, Default)]
 pub struct MetricDiffCountSumRows {
     pub table_name: String,
+    #[allow(dead_code)]
     pub namespace: String,
+    #[allow(dead_code)]
     pub first_snapshot_time: DateTime<Local>,
     pub first_count: u64,
     pub first_sum: u64,
@@ -258,4 +260,51 @@ pub struct MetricEntityDiff {
     pub btreemetricdiffvalue: BTreeMetricDiffValues,
     pub btreemetricdiffcountsum: BTreeMetricDiffCountSum,
     pub btreemetricdiffcountsumrows: BTreeMetricDiffCountSumRows,
-}
\ No newline at end of file
+    /// The hostname_port of the servers for which the server_uptime_ms statistic went down.
+    pub restarted_servers: BTreeSet<String>,
+}
+/// The ratios derived from the rocksdb statistics of a server, or of a table on a server.
+///
+/// A ratio is None if it cannot be calculated because its denominator is zero.
+#[derive(Debug, Default, PartialEq)]
+pub struct RocksDbRatios {
+    pub cache_hit_percentage: Option<f64>,
+    pub bloom_useful_percentage: Option<f64>,
+    pub write_amplification: Option<f64>,
+    pub read_amplification: Option<f64>,
+    pub stall_milliseconds: f64,
+    /// A rocksdb statistic had a value in the first snapshot, but not in the second snapshot,
+    /// so the differences, and thus the ratios, are not available.
+    pub unavailable: bool,
+}
+/// The key fields for the cdc and cdcsdk statistics per stream are: `stream_id`, `metric_type`, `table` (namespace.table_name), `metric_name`
+pub type CdcStreamKey = (String, String, String, String);
+/// BTreeMap for storing the cdc and cdcsdk statistics per stream.
+type BTreeCdcDiff = BTreeMap<CdcStreamKey, CdcDiffValues>;
+/// The struct that holds the first and second snapshot statistics of a stream and table.
+///
+/// The lag statistics are gauges, and contain the highest value found for all tablets of the table on all servers.
+/// All other statistics are counters, and contain the sum of all tablets of the table on all servers.
+/// For a countsum statistic, the value is the total_sum.
+#[derive(Debug, Default)]
+pub struct CdcDiffValues {
+    pub first_snapshot_time: DateTime<Local>,
+    pub first_value: i64,
+    pub second_snapshot_time: DateTime<Local>,
+    pub second_value: i64,
+}
+/// Wrapper struct for holding the cdc diff btreemap.
+#[derive(Debug, Default)]
+pub struct CdcDiff {
+    pub btreecdcdiff: BTreeCdcDiff,
+}
+/// The endpoint to read the metrics from (`--metrics-source`).
+#[derive(ValueEnum, Debug, Clone, PartialEq, Eq)]
+pub enum MetricsSource {
+    /// `/metrics`
+    Json,
+    /// `/prometheus-metrics`
+    Prometheus,
+    /// `/metrics`, and `/prometheus-metrics` if `/metrics` did not return any metrics.
+    Auto,
+}
@@ -5,6 +5,7 @@ use log::*;
 /// This struct is used in [ValueStatistics.valuestatisticdetails], which holds a HashMap with the statistic name as key and this struct as value.
 #[derive(Debug, Clone)]
 pub struct ValueStatisticDetails {
+    #[allow(dead_code)]
     pub unit: String,
     pub unit_suffix: String,
     pub stat_type: String,
@@ -23,7 +24,7 @@ impl ValueStatistics {
         argument: &str
     ) -> &ValueStatisticDetails
     {
-        match self.valuestatisticdetails.get(&argument.to_string())
+        match self.valuestatisticdetails.get(argument)
         {
             Some(lookup) => lookup,
             None =>
@@ -1,14 +1,16 @@
 //! The impls and functions
 //! 
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::{BTreeMap, BTreeSet}, fs, sync::mpsc::channel, time::{Duration, Instant}};
+use tokio::time;
 use chrono::{DateTime, Local};
 use prometheus_parse::Value;
 use regex::Regex;
 use log::*;
-use anyhow::Result;
+use colored::*;
+use anyhow::{bail, Context, Result};
 use crate::utility;
 use crate::snapshot;
-use crate::node_exporter::{NodeExporter, AllNodeExporter, NodeExporterDiff, NameCategoryDiff};
+use crate::node_exporter::{NodeExporter, AllNodeExporter, NodeExporterDiff, NameCategoryDiff, LabelDiff, BucketDiff, MemorySummary, NetworkSummary, PrometheusTarget};
 use crate::Opts;
 
 impl AllNodeExporter {
@@ -75,9 +77,72 @@ impl AllNodeExporter {
         port: &str,
     ) -> Vec<NodeExporter>
     {
-        let data_from_http = utility::http_get(host, port, "metrics");
+        AllNodeExporter::read_http_path(host, port, "metrics")
+    }
+    fn read_http_path(
+        host: &str,
+        port: &str,
+        path: &str,
+    ) -> Vec<NodeExporter>
+    {
+        let data_from_http = utility::http_get(host, port, path);
         AllNodeExporter::parse_nodeexporter(data_from_http)
     }
+    /// Scrape the additional prometheus targets in parallel, and save each target in its own snapshot file.
+    pub async fn perform_prometheus_targets_snapshot(
+        targets: &Vec<PrometheusTarget>,
+        snapshot_number: i32,
+        parallel: usize,
+    ) -> Result<()>
+    {
+        info!("begin snapshot");
+        let timer = Instant::now();
+
+        for (target, allnodeexporter) in AllNodeExporter::read_prometheus_targets(targets, parallel).await
+        {
+            snapshot::save_snapshot_json(snapshot_number, format!("{}{}", PROMETHEUS_TARGET_PREFIX, target).as_str(), allnodeexporter.nodeexporter)?;
+        }
+
+        info!("end snapshot: {:?}", timer.elapsed());
+        Ok(())
+    }
+    /// Read the prometheus targets in parallel, and return the data per target name.
+    pub async fn read_prometheus_targets(
+        targets: &Vec<PrometheusTarget>,
+        parallel: usize,
+    ) -> BTreeMap<String, AllNodeExporter>
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel).build().unwrap();
+        let (tx, rx) = channel();
+        pool.scope(move |s| {
+            for target in targets {
+                let tx = tx.clone();
+                s.spawn(move |_| {
+                    let detail_snapshot_time = Local::now();
+                    let mut nodeexporter = AllNodeExporter::read_http_path(&target.host, &target.port, &target.path);
+                    nodeexporter.iter_mut().for_each(|r| r.timestamp = detail_snapshot_time);
+                    nodeexporter.iter_mut().for_each(|r| r.hostname_port = format!("{}:{}", target.host, target.port));
+                    tx.send((target.name.clone(), nodeexporter)).expect("error sending data via tx");
+                });
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        let mut targets: BTreeMap<String, AllNodeExporter> = BTreeMap::new();
+        for (name, nodeexporters) in rx
+        {
+            targets.entry(name)
+                .or_default()
+                .nodeexporter
+                .extend(nodeexporters);
+        }
+
+        targets
+    }
     fn parse_nodeexporter( 
         node_exporter_data: String
     ) -> Vec<NodeExporter>
@@ -132,9 +197,8 @@ impl AllNodeExporter {
                         )
                     },
                     Value::Untyped(val) => {
-                        // it turns out summary type _sum and _count values are untyped values.
-                        // so I remove them here.
-                        if sample.metric.ends_with("_sum") || sample.metric.ends_with("_count") { continue };
+                        // the _sum and _count values of histograms and summaries are untyped values.
+                        // these are counters, and are used to calculate the average.
                         // untyped: not sure what it is.
                         // I would say: probably a counter.
                         nodeexporter.push(
@@ -149,8 +213,40 @@ impl AllNodeExporter {
                             }
                         )
                     },
-                    Value::Histogram(_val) => {},
-                    Value::Summary(_val) => {},
+                    // A histogram is stored as a row per bucket, holding the cumulative count of the bucket.
+                    Value::Histogram(val) => {
+                        for bucket in val {
+                            nodeexporter.push(
+                                NodeExporter {
+                                    name: sample.metric.to_string(),
+                                    exporter_type: "histogram".to_string(),
+                                    labels: format!("{}_le_{}", label, bucket.less_than),
+                                    category: "all".to_string(),
+                                    exporter_timestamp: sample.timestamp,
+                                    value: bucket.count,
+                                    bucket: Some(bucket.less_than),
+                                    ..Default::default()
+                                }
+                            )
+                        }
+                    },
+                    // A summary is stored as a row per quantile, holding the value of the quantile.
+                    Value::Summary(val) => {
+                        for quantile in val {
+                            nodeexporter.push(
+                                NodeExporter {
+                                    name: sample.metric.to_string(),
+                                    exporter_type: "summary".to_string(),
+                                    labels: format!("{}_quantile_{}", label, quantile.quantile),
+                                    category: "all".to_string(),
+                                    exporter_timestamp: sample.timestamp,
+                                    value: quantile.count,
+                                    bucket: Some(quantile.quantile),
+                                    ..Default::default()
+                                }
+                            )
+                        }
+                    },
                 }
             }
             // post processing
@@ -173,15 +269,25 @@ impl NodeExporterDiff {
         end_snapshot: &String,
         begin_snapshot_time: &DateTime<Local>,
     ) -> Result<NodeExporterDiff>
+    {
+        NodeExporterDiff::snapshot_diff_file(begin_snapshot, end_snapshot, begin_snapshot_time, "nodeexporter")
+    }
+    /// Create the diff of a prometheus format snapshot file, such as `nodeexporter` or `prometheus_<name>`.
+    pub fn snapshot_diff_file (
+        begin_snapshot: &String,
+        end_snapshot: &String,
+        begin_snapshot_time: &DateTime<Local>,
+        filename: &str,
+    ) -> Result<NodeExporterDiff>
     {
         let mut nodeexporterdiff = NodeExporterDiff::new();
 
         let mut allnodeexporter = AllNodeExporter::new();
-        allnodeexporter.nodeexporter = snapshot::read_snapshot_json(begin_snapshot, "nodeexporter")?;
+        allnodeexporter.nodeexporter = snapshot::read_snapshot_json(begin_snapshot, filename)?;
         nodeexporterdiff.first_snapshot(allnodeexporter);
 
         let mut allnodeexporter = AllNodeExporter::new();
-        allnodeexporter.nodeexporter = snapshot::read_snapshot_json(end_snapshot, "nodeexporter")?;
+        allnodeexporter.nodeexporter = snapshot::read_snapshot_json(end_snapshot, filename)?;
         nodeexporterdiff.second_snapshot(allnodeexporter, begin_snapshot_time);
 
         Ok(nodeexporterdiff)
@@ -207,6 +313,7 @@ impl NodeExporterDiff {
                     exporter_type: row.exporter_type.clone(),
                     category: row.category.clone(),
                     first_value: row.value,
+                    bucket: row.bucket,
                     ..Default::default()
                 });
         }
@@ -231,9 +338,19 @@ impl NodeExporterDiff {
                     exporter_type: row.exporter_type.clone(),
                     category: row.category.clone(),
                     second_value: row.value,
+                    bucket: row.bucket,
                     ..Default::default()
                 });
         }
+        // a machine that has been rebooted has a different boot time.
+        self.restarted_servers = self.btreemapnodeexporterdiff
+            .iter()
+            .filter(|((_, name, _), row)| name == "node_boot_time_seconds"
+                && row.first_value != 0.0
+                && row.second_value != 0.0
+                && row.first_value != row.second_value)
+            .map(|((hostname_port, _, _), _)| hostname_port.clone())
+            .collect();
     }
     pub fn print(
         &self,
@@ -243,20 +360,23 @@ impl NodeExporterDiff {
         details_enable: &bool,
     )
     {
+        utility::print_restarts(&self.restarted_servers, hostname_filter);
         for ((hostname_port, name, category), diff_row) in &self.btreemapnodeexporterdiff {
+            let (difference, reset) = utility::counter_difference(diff_row.first_value, diff_row.second_value, self.restarted_servers.contains(hostname_port));
             if hostname_filter.is_match(hostname_port)
                 && stat_name_filter.is_match(name)
-                && diff_row.second_value - diff_row.first_value != 0.0
+                && difference != 0.0
                 && diff_row.exporter_type == "counter"
             {
                 if *details_enable && category == "summary" { continue };
                 if ! *details_enable && diff_row.category == "detail" { continue };
-                println!("{:20} {:8} {:73} {:19.6} {:15.3} /s",
+                println!("{:20} {:8} {:73} {:19.6} {:15.3} /s {}",
                          hostname_port,
                          diff_row.exporter_type,
                          format!("{}{}", name, category),
-                         diff_row.second_value - diff_row.first_value,
-                         (diff_row.second_value - diff_row.first_value) / (diff_row.second_snapshot_time - diff_row.first_snapshot_time).num_seconds() as f64,
+                         difference,
+                         difference / (diff_row.second_snapshot_time - diff_row.first_snapshot_time).num_seconds() as f64,
+                         if reset { "reset".red() } else { "".normal() },
                 );
             }
             if hostname_filter.is_match(hostname_port)
@@ -275,6 +395,355 @@ impl NodeExporterDiff {
                 );
             }
         }
+        self.print_buckets(hostname_filter, stat_name_filter, gauges_enable, details_enable);
+    }
+    /// Group the histogram and summary rows by host, name and the labels without the bucket suffix.
+    ///
+    /// For a histogram, the bucket counts are the difference between the snapshots.
+    /// For a summary, the quantile values are the values of the second snapshot, because quantiles cannot be subtracted.
+    ///
+    /// Rows that are put in category 'detail', such as the go_ statistics of node_exporter itself,
+    /// are only included if details_enable is set.
+    fn group_buckets(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        details_enable: &bool,
+    ) -> BTreeMap<(String, String, String), BucketDiff>
+    {
+        let mut histograms: BTreeMap<(String, String, String), BucketDiff> = BTreeMap::new();
+        for ((hostname_port, name, labels), diff_row) in self.btreemapnodeexporterdiff
+            .iter()
+            .filter(|((hostname_port, name, _), diff_row)| (diff_row.exporter_type == "histogram" || diff_row.exporter_type == "summary")
+                && hostname_filter.is_match(hostname_port)
+                && stat_name_filter.is_match(name)
+                && (*details_enable || diff_row.category != "detail"))
+        {
+            let (Some(bucket), Some((labels, _))) = (diff_row.bucket, labels.rsplit_once(if diff_row.exporter_type == "histogram" { "_le_" } else { "_quantile_" })) else { continue };
+            let value = if diff_row.exporter_type == "histogram" {
+                utility::counter_difference(diff_row.first_value, diff_row.second_value, self.restarted_servers.contains(hostname_port)).0
+            } else {
+                diff_row.second_value
+            };
+            let bucketdiff = histograms.entry((hostname_port.to_string(), name.to_string(), labels.to_string())).or_default();
+            bucketdiff.exporter_type = diff_row.exporter_type.clone();
+            bucketdiff.buckets.push((bucket, value));
+        }
+        for bucketdiff in histograms.values_mut() {
+            bucketdiff.buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        histograms
+    }
+    /// Print the histograms and summaries.
+    ///
+    /// For a histogram, the number of observations and the approximated 50th, 90th and 99th percentiles of the observations
+    /// between the snapshots are shown; if details are enabled, the bucket differences are shown too.
+    /// For a summary, the quantiles of the second snapshot are shown; these are values, like gauges, and are only shown if gauges_enable is set.
+    /// If the `_sum` and `_count` rows are present, the average of the observations between the snapshots is shown.
+    fn print_buckets(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        gauges_enable: &bool,
+        details_enable: &bool,
+    )
+    {
+        for ((hostname_port, name, labels), bucketdiff) in self.group_buckets(hostname_filter, stat_name_filter, details_enable)
+        {
+            if bucketdiff.exporter_type == "summary" && !*gauges_enable { continue };
+            let difference = |suffix: &str| -> f64 {
+                match self.btreemapnodeexporterdiff.get(&(hostname_port.clone(), format!("{}{}", name, suffix), labels.clone())) {
+                    Some(diff_row) => utility::counter_difference(diff_row.first_value, diff_row.second_value, self.restarted_servers.contains(&hostname_port)).0,
+                    None => 0.0,
+                }
+            };
+            let average = if difference("_count") == 0.0 { 0.0 } else { difference("_sum") / difference("_count") };
+            if bucketdiff.exporter_type == "histogram"
+            {
+                let count = bucketdiff.buckets.last().map(|(_, count)| *count).unwrap_or_default();
+                if count == 0.0 { continue };
+                println!("{:20} {:8} {:73} count: {:.0} avg: {:.6} p50: {:.6} p90: {:.6} p99: {:.6}",
+                         hostname_port,
+                         bucketdiff.exporter_type,
+                         format!("{}{}", name, labels),
+                         count,
+                         average,
+                         histogram_quantile(0.5, &bucketdiff.buckets),
+                         histogram_quantile(0.9, &bucketdiff.buckets),
+                         histogram_quantile(0.99, &bucketdiff.buckets),
+                );
+                if *details_enable
+                {
+                    for (less_than, count) in &bucketdiff.buckets
+                    {
+                        println!("{:20} {:8} {:73} {:19.6}",
+                                 hostname_port,
+                                 "bucket",
+                                 format!("{}{}_le_{}", name, labels, less_than),
+                                 count,
+                        );
+                    }
+                }
+            }
+            else
+            {
+                println!("{:20} {:8} {:73} avg: {:.6} {}",
+                         hostname_port,
+                         bucketdiff.exporter_type,
+                         format!("{}{}", name, labels),
+                         average,
+                         bucketdiff.buckets
+                             .iter()
+                             .map(|(quantile, value)| format!("q{}: {:.6}", quantile, value))
+                             .collect::<Vec<String>>()
+                             .join(" "),
+                );
+            }
+        }
+    }
+    /// Group the counter differences of the statistics starting with `prefix` by host and label.
+    /// The leading underscore of the label is removed, so that the label is the device or interface name.
+    ///
+    /// Labels that are put in category 'detail', such as the device mapper devices by [linux_dm_to_detail],
+    /// are only included if details_enable is set.
+    fn group_by_label(
+        &self,
+        prefix: &str,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    ) -> BTreeMap<(String, String), LabelDiff>
+    {
+        let mut labels: BTreeMap<(String, String), LabelDiff> = BTreeMap::new();
+        for ((hostname_port, name, label), diff_row) in self.btreemapnodeexporterdiff
+            .iter()
+            .filter(|((hostname_port, name, _), diff_row)| name.starts_with(prefix)
+                && hostname_filter.is_match(hostname_port)
+                && diff_row.exporter_type == "counter"
+                // the statistic wasn't filled out for the second snapshot.
+                && diff_row.second_snapshot_time != DateTime::<Local>::default())
+        {
+            if !*details_enable && diff_row.category == "detail" { continue };
+            let (difference, _) = utility::counter_difference(diff_row.first_value, diff_row.second_value, self.restarted_servers.contains(hostname_port));
+            let labeldiff = labels.entry((hostname_port.to_string(), label.trim_start_matches('_').to_string())).or_default();
+            labeldiff.seconds = (diff_row.second_snapshot_time - diff_row.first_snapshot_time).num_milliseconds() as f64 / 1000_f64;
+            labeldiff.differences.insert(name.to_string(), difference);
+        }
+        labels
+    }
+    /// Print an iostat-like overview of the disk statistics per host and device.
+    ///
+    /// - r/s, w/s: node_disk_reads_completed_total, node_disk_writes_completed_total per second.
+    /// - rkB/s, wkB/s: node_disk_read_bytes_total, node_disk_written_bytes_total in kilobytes per second.
+    /// - areq-sz: the average size of a request in kilobytes.
+    /// - r_await, w_await: node_disk_read_time_seconds_total, node_disk_write_time_seconds_total per request in milliseconds.
+    /// - aqu-sz: node_disk_io_time_weighted_seconds_total per second, which is the average queue depth.
+    /// - %util: node_disk_io_time_seconds_total per second, which is the percentage of time the device was busy.
+    ///
+    /// Devices without any requests between the snapshots are not shown.
+    pub fn print_iostat(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        utility::print_restarts(&self.restarted_servers, hostname_filter);
+        println!("{:20} {:12} {:>10} {:>10} {:>12} {:>12} {:>10} {:>10} {:>10} {:>8} {:>7}",
+                 "hostname_port", "device", "r/s", "w/s", "rkB/s", "wkB/s", "areq-sz", "r_await", "w_await", "aqu-sz", "%util");
+        for ((hostname_port, device), labeldiff) in self.group_by_label("node_disk_", hostname_filter, details_enable)
+        {
+            let value = |name: &str| -> f64 { *labeldiff.differences.get(name).unwrap_or(&0.0) };
+            let per_request = |numerator: f64, requests: f64| -> f64 { if requests == 0.0 { 0.0 } else { numerator / requests } };
+            let reads = value("node_disk_reads_completed_total");
+            let writes = value("node_disk_writes_completed_total");
+            if reads + writes == 0.0 || labeldiff.seconds == 0.0 { continue };
+            println!("{:20} {:12} {:10.2} {:10.2} {:12.2} {:12.2} {:10.2} {:10.2} {:10.2} {:8.2} {:7.2}",
+                     hostname_port,
+                     device,
+                     reads / labeldiff.seconds,
+                     writes / labeldiff.seconds,
+                     value("node_disk_read_bytes_total") / 1024_f64 / labeldiff.seconds,
+                     value("node_disk_written_bytes_total") / 1024_f64 / labeldiff.seconds,
+                     per_request((value("node_disk_read_bytes_total") + value("node_disk_written_bytes_total")) / 1024_f64, reads + writes),
+                     per_request(value("node_disk_read_time_seconds_total") * 1000_f64, reads),
+                     per_request(value("node_disk_write_time_seconds_total") * 1000_f64, writes),
+                     value("node_disk_io_time_weighted_seconds_total") / labeldiff.seconds,
+                     value("node_disk_io_time_seconds_total") / labeldiff.seconds * 100_f64,
+            );
+        }
+    }
+    /// Print a compact overview of the CPU, memory and network usage per host.
+    pub fn print_summary(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        utility::print_restarts(&self.restarted_servers, hostname_filter);
+        self.print_cpu(hostname_filter);
+        self.print_memory(hostname_filter);
+        self.print_network(hostname_filter, details_enable);
+    }
+    /// Calculate the CPU time per mode as a percentage of the total CPU time of all CPUs of a host.
+    /// This uses the node_cpu_seconds_total rows summed for all CPUs by [linux_cpu_sum].
+    ///
+    /// Hosts without CPU time between the snapshots are not included.
+    fn cpu_percentages(
+        &self,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<String, BTreeMap<String, f64>>
+    {
+        // hostname_port -> mode -> seconds
+        let mut hosts: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+        for ((hostname_port, mode), labeldiff) in self.group_by_label("node_cpu_seconds_total", hostname_filter, &false)
+        {
+            hosts.entry(hostname_port)
+                .or_default()
+                .insert(mode, *labeldiff.differences.get("node_cpu_seconds_total").unwrap_or(&0.0));
+        }
+        hosts.into_iter()
+            .filter_map(|(hostname_port, modes)| {
+                let total: f64 = modes.values().sum();
+                if total == 0.0 { return None };
+                Some((hostname_port, modes.into_iter().map(|(mode, seconds)| (mode, seconds / total * 100_f64)).collect()))
+            })
+            .collect()
+    }
+    /// Print the CPU time per mode as a percentage of the total CPU time of all CPUs of a host, see [NodeExporterDiff::cpu_percentages].
+    fn print_cpu(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        println!("{:20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                 "hostname_port", "%user", "%nice", "%system", "%iowait", "%irq", "%softirq", "%steal", "%idle");
+        for (hostname_port, modes) in self.cpu_percentages(hostname_filter)
+        {
+            let percentage = |mode: &str| -> f64 { *modes.get(mode).unwrap_or(&0.0) };
+            println!("{:20} {:8.2} {:8.2} {:8.2} {:8.2} {:8.2} {:8.2} {:8.2} {:8.2}",
+                     hostname_port,
+                     percentage("user"),
+                     percentage("nice"),
+                     percentage("system"),
+                     percentage("iowait"),
+                     percentage("irq"),
+                     percentage("softirq"),
+                     percentage("steal"),
+                     percentage("idle"),
+            );
+        }
+    }
+    /// Calculate the memory usage at the time of the second snapshot, and the swap activity between the snapshots.
+    ///
+    /// - used: node_memory_MemTotal_bytes minus node_memory_MemAvailable_bytes.
+    /// - cached: node_memory_Cached_bytes plus node_memory_Buffers_bytes.
+    /// - swap used: node_memory_SwapTotal_bytes minus node_memory_SwapFree_bytes.
+    /// - si/s, so/s: node_vmstat_pswpin, node_vmstat_pswpout pages per second.
+    fn memory_summary(
+        &self,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<String, MemorySummary>
+    {
+        let hosts: BTreeSet<&String> = self.btreemapnodeexporterdiff
+            .keys()
+            .filter(|(hostname_port, name, _)| name == "node_memory_MemTotal_bytes" && hostname_filter.is_match(hostname_port))
+            .map(|(hostname_port, _, _)| hostname_port)
+            .collect();
+        let mut memory: BTreeMap<String, MemorySummary> = BTreeMap::new();
+        for hostname_port in hosts
+        {
+            let row = |name: &str| self.btreemapnodeexporterdiff.get(&(hostname_port.to_string(), name.to_string(), String::new()));
+            let gauge = |name: &str| -> f64 { row(name).map(|row| row.second_value).unwrap_or_default() / (1024_f64 * 1024_f64) };
+            let per_second = |name: &str| -> f64 {
+                match row(name) {
+                    Some(row) if row.second_snapshot_time != DateTime::<Local>::default() => {
+                        let (difference, _) = utility::counter_difference(row.first_value, row.second_value, self.restarted_servers.contains(hostname_port));
+                        let seconds = (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 / 1000_f64;
+                        if seconds == 0.0 { 0.0 } else { difference / seconds }
+                    },
+                    _ => 0.0,
+                }
+            };
+            memory.insert(hostname_port.to_string(), MemorySummary {
+                total_mb: gauge("node_memory_MemTotal_bytes"),
+                used_mb: gauge("node_memory_MemTotal_bytes") - gauge("node_memory_MemAvailable_bytes"),
+                cached_mb: gauge("node_memory_Cached_bytes") + gauge("node_memory_Buffers_bytes"),
+                available_mb: gauge("node_memory_MemAvailable_bytes"),
+                swap_used_mb: gauge("node_memory_SwapTotal_bytes") - gauge("node_memory_SwapFree_bytes"),
+                swap_in_per_second: per_second("node_vmstat_pswpin"),
+                swap_out_per_second: per_second("node_vmstat_pswpout"),
+            });
+        }
+        memory
+    }
+    /// Print the memory usage per host, see [NodeExporterDiff::memory_summary].
+    fn print_memory(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        println!("{:20} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10} {:>10}",
+                 "hostname_port", "total MB", "used MB", "cached MB", "available MB", "swap used MB", "si/s", "so/s");
+        for (hostname_port, memory) in self.memory_summary(hostname_filter)
+        {
+            println!("{:20} {:12.0} {:12.0} {:12.0} {:12.0} {:12.0} {:10.2} {:10.2}",
+                     hostname_port,
+                     memory.total_mb,
+                     memory.used_mb,
+                     memory.cached_mb,
+                     memory.available_mb,
+                     memory.swap_used_mb,
+                     memory.swap_in_per_second,
+                     memory.swap_out_per_second,
+            );
+        }
+    }
+    /// Calculate the received and transmitted bytes, packets and errors per second per host and network interface.
+    /// Interfaces without any traffic between the snapshots are not included.
+    fn network_summary(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    ) -> BTreeMap<(String, String), NetworkSummary>
+    {
+        let mut network: BTreeMap<(String, String), NetworkSummary> = BTreeMap::new();
+        for ((hostname_port, interface), labeldiff) in self.group_by_label("node_network_", hostname_filter, details_enable)
+        {
+            let per_second = |name: &str| -> f64 { labeldiff.differences.get(name).unwrap_or(&0.0) / labeldiff.seconds };
+            if labeldiff.seconds == 0.0
+                || per_second("node_network_receive_packets_total") + per_second("node_network_transmit_packets_total") == 0.0 { continue };
+            network.insert((hostname_port, interface), NetworkSummary {
+                receive_kb_per_second: per_second("node_network_receive_bytes_total") / 1024_f64,
+                transmit_kb_per_second: per_second("node_network_transmit_bytes_total") / 1024_f64,
+                receive_packets_per_second: per_second("node_network_receive_packets_total"),
+                transmit_packets_per_second: per_second("node_network_transmit_packets_total"),
+                receive_errors_per_second: per_second("node_network_receive_errs_total"),
+                transmit_errors_per_second: per_second("node_network_transmit_errs_total"),
+            });
+        }
+        network
+    }
+    /// Print the network traffic per host and network interface, see [NodeExporterDiff::network_summary].
+    fn print_network(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        println!("{:20} {:12} {:>12} {:>12} {:>10} {:>10} {:>8} {:>8}",
+                 "hostname_port", "interface", "rxkB/s", "txkB/s", "rxpck/s", "txpck/s", "rxerr/s", "txerr/s");
+        for ((hostname_port, interface), network) in self.network_summary(hostname_filter, details_enable)
+        {
+            println!("{:20} {:12} {:12.2} {:12.2} {:10.2} {:10.2} {:8.2} {:8.2}",
+                     hostname_port,
+                     interface,
+                     network.receive_kb_per_second,
+                     network.transmit_kb_per_second,
+                     network.receive_packets_per_second,
+                     network.transmit_packets_per_second,
+                     network.receive_errors_per_second,
+                     network.transmit_errors_per_second,
+            );
+        }
     }
     pub async fn adhoc_read_first_snapshot (
         &mut self,
@@ -298,6 +767,103 @@ impl NodeExporterDiff {
         self.second_snapshot(allnodeexporter, first_snapshot_time);
     }
 }
+/// The prefix of the snapshot files of the prometheus targets.
+const PROMETHEUS_TARGET_PREFIX: &str = "prometheus_";
+
+impl PrometheusTarget {
+    /// Parse a target in the format `name=host:port/path`.
+    ///
+    /// The name is used in the snapshot filename, and therefore can only contain letters, digits, '_' and '-'.
+    pub fn parse(target: &str) -> Result<PrometheusTarget>
+    {
+        let Some((name, address)) = target.split_once('=') else {
+            bail!("Invalid prometheus target: {}, the format is name=host:port/path", target);
+        };
+        if !name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_' || character == '-') {
+            bail!("Invalid prometheus target name: {}, the name can only contain letters, digits, '_' and '-'", name);
+        }
+        let (host_port, path) = address.split_once('/').unwrap_or((address, "metrics"));
+        let Some((host, port)) = host_port.rsplit_once(':') else {
+            bail!("Invalid prometheus target: {}, the format is name=host:port/path", target);
+        };
+        if name.is_empty() || host.is_empty() || port.is_empty() {
+            bail!("Invalid prometheus target: {}, the format is name=host:port/path", target);
+        }
+        Ok(PrometheusTarget {
+            name: name.to_string(),
+            host: host.to_string(),
+            port: port.to_string(),
+            path: path.to_string(),
+        })
+    }
+    /// Parse all targets set with `--prometheus-targets`, see [PrometheusTarget::parse].
+    /// Parse the prometheus targets, which must have unique names, because the name is used for the snapshot file.
+    pub fn parse_targets(targets: &[&str]) -> Result<Vec<PrometheusTarget>>
+    {
+        let targets = targets
+            .iter()
+            .map(|target| PrometheusTarget::parse(target))
+            .collect::<Result<Vec<PrometheusTarget>>>()?;
+        let mut names = BTreeSet::new();
+        for target in &targets
+        {
+            if !names.insert(&target.name) {
+                bail!("Duplicate prometheus target name: {}, every prometheus target needs a unique name", target.name);
+            }
+        }
+        Ok(targets)
+    }
+}
+/// Find the names of the prometheus targets that are stored in a snapshot.
+pub fn prometheus_targets_in_snapshot(
+    snapshot_number: &String,
+) -> Result<Vec<String>>
+{
+    let snapshot_directory = snapshot::snapshot_directory(snapshot_number)?;
+    let mut targets: Vec<String> = fs::read_dir(&snapshot_directory)
+        .with_context(|| format!("Error reading snapshot directory: {}", snapshot_directory.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|filename| filename
+            .strip_prefix(PROMETHEUS_TARGET_PREFIX)
+            .and_then(|filename| filename.strip_suffix(".json"))
+            .map(|name| name.to_string()))
+        .collect();
+    targets.sort();
+    Ok(targets)
+}
+
+/// Approximate a quantile from the cumulative bucket counts of a histogram, ordered by their upper bound.
+///
+/// This works in the same way as the prometheus histogram_quantile function:
+/// the bucket holding the quantile is found, and the value is linearly interpolated between the bounds of the bucket.
+/// If the quantile falls in the +Inf bucket, the upper bound of the highest finite bucket is returned.
+fn histogram_quantile(
+    quantile: f64,
+    buckets: &[(f64, f64)],
+) -> f64
+{
+    let total = match buckets.last() {
+        Some((_, count)) if *count > 0.0 => *count,
+        _ => return 0.0,
+    };
+    let rank = quantile * total;
+    let mut lower_bound = 0_f64;
+    let mut lower_count = 0_f64;
+    for (upper_bound, count) in buckets
+    {
+        if *count >= rank
+        {
+            if upper_bound.is_infinite() { return lower_bound };
+            if *count == lower_count { return *upper_bound };
+            return lower_bound + (upper_bound - lower_bound) * (rank - lower_count) / (count - lower_count);
+        }
+        lower_bound = *upper_bound;
+        lower_count = *count;
+    }
+    lower_bound
+}
+
 
 fn nodeexporter_statistics_to_detail(nodeexporter: &mut [NodeExporter])
 {
@@ -391,6 +957,7 @@ fn linux_softnet_sum(nodeexporter: &mut Vec<NodeExporter>)
 /// - schedstat_waiting (task runnable, but not running on CPU, waiting for runtime)
 /// - schedstat_running (task runnable and running on CPU)
 /// - schedstat_timeslices (the number of timeslices executed)
+///
 /// The original values are kept, but put in category 'detail'.
 /// The summarized values are put in a category 'summary'.
 fn linux_schedstat_sum(nodeexporter: &mut Vec<NodeExporter>)
@@ -453,7 +1020,7 @@ fn linux_schedstat_sum(nodeexporter: &mut Vec<NodeExporter>)
 /// - softirq   : amount of time running deferrable functions (non-urgent interruptable kernel functions).
 /// - idle      : amount of time NOT running.
 /// - iowait    : this is a special case. There is no IO wait time in the kernel. Instead for regular, buffered, IO, linux keeps a counter of outstanding IOs, and tries to map idle time to these, based on idle time availability.
-///               More advanced IO interfaces mostly do not increase this counter, such as io_submit/io_getevents.
+///   More advanced IO interfaces mostly do not increase this counter, such as io_submit/io_getevents.
 /// - steal     : this too is a special case: this is the time the hypervisor did not get CPU slices. Higher values for this could indicate CPU oversubscription by the hypervisor.
 ///
 /// The original values are kept, but put in category 'detail'.
@@ -562,6 +1129,146 @@ pub async fn node_exporter_diff(
     Ok(())
 }
 
+pub async fn iostat_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let nodeexporterdiff = NodeExporterDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
+    nodeexporterdiff.print_iostat(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+/// Read node_exporter every interval seconds, and print the difference with the previous read using print.
+///
+/// This keeps running until it's stopped.
+pub async fn node_exporter_live<F>(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    interval_seconds: u64,
+    print: F,
+) -> Result<()>
+where
+    F: Fn(&NodeExporterDiff),
+{
+    let mut interval = time::interval(Duration::from_secs(interval_seconds.max(1)));
+    // the first tick completes immediately.
+    interval.tick().await;
+    let mut previous_allnodeexporter = AllNodeExporter::read_nodeexporter(&hosts, &ports, parallel).await;
+    loop
+    {
+        interval.tick().await;
+        let allnodeexporter = AllNodeExporter::read_nodeexporter(&hosts, &ports, parallel).await;
+        let first_snapshot_time = previous_allnodeexporter.nodeexporter
+            .iter()
+            .map(|nodeexporter| nodeexporter.timestamp)
+            .min()
+            .unwrap_or_else(Local::now);
+        let mut nodeexporterdiff = NodeExporterDiff::new();
+        nodeexporterdiff.first_snapshot(previous_allnodeexporter);
+        nodeexporterdiff.second_snapshot(allnodeexporter.clone(), &first_snapshot_time);
+        println!("{}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+        print(&nodeexporterdiff);
+        previous_allnodeexporter = allnodeexporter;
+    }
+}
+
+pub async fn iostat_live(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let interval_seconds = options.iostat_live.unwrap();
+    node_exporter_live(hosts, ports, parallel, interval_seconds, |nodeexporterdiff| nodeexporterdiff.print_iostat(&hostname_filter, &options.details_enable)).await
+}
+
+pub async fn node_summary_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let nodeexporterdiff = NodeExporterDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
+    nodeexporterdiff.print_summary(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+pub async fn node_summary_live(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let interval_seconds = options.node_summary_live.unwrap();
+    node_exporter_live(hosts, ports, parallel, interval_seconds, |nodeexporterdiff| nodeexporterdiff.print_summary(&hostname_filter, &options.details_enable)).await
+}
+
+/// Print the difference of the prometheus targets that are stored in the begin snapshot.
+pub fn print_prometheus_targets_diff(
+    begin_snapshot: &String,
+    end_snapshot: &String,
+    begin_snapshot_time: &DateTime<Local>,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let begin_targets = prometheus_targets_in_snapshot(begin_snapshot)?;
+    let end_targets = prometheus_targets_in_snapshot(end_snapshot)?;
+    for target in end_targets.iter().filter(|target| !begin_targets.contains(target))
+    {
+        warn!("prometheus target {} is not in begin snapshot {}, skipping", target, begin_snapshot);
+    }
+    for target in begin_targets
+    {
+        if !end_targets.contains(&target) {
+            warn!("prometheus target {} is not in end snapshot {}, skipping", target, end_snapshot);
+            continue;
+        }
+        let nodeexporterdiff = NodeExporterDiff::snapshot_diff_file(begin_snapshot, end_snapshot, begin_snapshot_time, format!("{}{}", PROMETHEUS_TARGET_PREFIX, target).as_str())?;
+        println!("prometheus target: {}", target.bold());
+        nodeexporterdiff.print(&hostname_filter, &stat_name_filter, &options.gauges_enable, &options.details_enable);
+    }
+    Ok(())
+}
+
+pub async fn prometheus_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    print_prometheus_targets_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp, options)
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -649,7 +1356,12 @@ mod tests {
         go_gc_duration_seconds_count 11
         "#.to_string();
         let result = AllNodeExporter::parse_nodeexporter(fake_http_data);
-        assert_eq!(result.len(), 0);
+        // 5 quantiles, _sum and _count
+        assert_eq!(result.len(), 7);
+        let quantiles: Vec<&NodeExporter> = result.iter().filter(|r| r.exporter_type == "summary").collect();
+        assert_eq!(quantiles.len(), 5);
+        assert!(quantiles.iter().any(|r| r.labels == "_quantile_0.5" && r.bucket == Some(0.5) && r.value == 4.0984e-05));
+        assert!(result.iter().any(|r| r.name == "go_gc_duration_seconds_count" && r.value == 11.0));
     }
 
     #[test]
@@ -676,7 +1388,328 @@ request_duration_count 3.0
 request_duration_sum 22.978489699999997
         "#.to_string();
         let result = AllNodeExporter::parse_nodeexporter(fake_http_data);
-        assert_eq!(result.len(), 0);
+        // 15 buckets, _sum and _count
+        assert_eq!(result.len(), 17);
+        let buckets: Vec<&NodeExporter> = result.iter().filter(|r| r.exporter_type == "histogram").collect();
+        assert_eq!(buckets.len(), 15);
+        assert!(buckets.iter().all(|r| r.name == "request_duration"));
+        assert!(buckets.iter().any(|r| r.labels == "_le_10" && r.bucket == Some(10.0) && r.value == 3.0));
+        assert!(buckets.iter().any(|r| r.bucket == Some(f64::INFINITY) && r.value == 3.0));
+    }
+
+    #[test]
+    fn unit_node_exporter_snapshot_round_trip() {
+        // the +Inf bucket and the NaN quantile have no JSON representation, and must survive writing and reading the snapshot JSON.
+        let fake_http_data = r#"
+# TYPE request_duration histogram
+request_duration_bucket{le="1.0",} 1.0
+request_duration_bucket{le="+Inf",} 3.0
+request_duration_count 3.0
+request_duration_sum 22.9
+# TYPE go_gc_duration_seconds summary
+go_gc_duration_seconds{quantile="0.5"} NaN
+go_gc_duration_seconds{quantile="1"} 0.000218416
+go_gc_duration_seconds_sum 0.000609084
+go_gc_duration_seconds_count 11
+        "#.to_string();
+        let mut nodeexporter = AllNodeExporter::parse_nodeexporter(fake_http_data);
+        nodeexporter.iter_mut().for_each(|r| r.hostname_port = "1.1.1.1:9300".to_string());
+
+        let json = serde_json::to_string(&nodeexporter).unwrap();
+        let result: Vec<NodeExporter> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(result.len(), nodeexporter.len());
+        assert!(result.iter().any(|r| r.name == "request_duration" && r.bucket == Some(f64::INFINITY) && r.value == 3.0));
+        assert!(result.iter().any(|r| r.name == "go_gc_duration_seconds" && r.bucket == Some(0.5) && r.value.is_nan()));
+        assert!(result.iter().any(|r| r.name == "request_duration_sum" && r.bucket.is_none() && r.value == 22.9));
+
+        // the +Inf bucket is still used for the count of the observations.
+        let mut nodeexporterdiff = NodeExporterDiff::new();
+        nodeexporterdiff.second_snapshot(AllNodeExporter { nodeexporter: result }, &Local::now());
+        let buckets = nodeexporterdiff.group_buckets(&utility::set_regex(&None), &utility::set_regex(&None), &true);
+        let histogram = buckets.get(&("1.1.1.1:9300".to_string(), "request_duration".to_string(), String::new())).unwrap();
+        assert_eq!(histogram.buckets, vec![(1.0, 1.0), (f64::INFINITY, 3.0)]);
+        // go_ statistics are in category detail, and are not grouped without details_enable.
+        assert!(nodeexporterdiff.group_buckets(&utility::set_regex(&None), &utility::set_regex(&None), &false)
+            .keys()
+            .all(|(_, name, _)| !name.starts_with("go_")));
+        // rows written as null by older versions are read as NaN and None.
+        let legacy: Vec<NodeExporter> = serde_json::from_str(r#"[{"hostname_port":"1.1.1.1:9300","timestamp":"2023-01-01T00:00:00+00:00","name":"request_duration","exporter_type":"histogram","labels":"_le_inf","category":"all","value":null,"exporter_timestamp":"2023-01-01T00:00:00Z","bucket":null}]"#).unwrap();
+        assert!(legacy[0].value.is_nan());
+        assert_eq!(legacy[0].bucket, None);
+    }
+
+    #[test]
+    fn unit_prometheus_target_parse() {
+        let result = PrometheusTarget::parse("cgroup=192.168.66.80:9101/cgroup/metrics").unwrap();
+        assert_eq!(result, PrometheusTarget { name: "cgroup".to_string(), host: "192.168.66.80".to_string(), port: "9101".to_string(), path: "cgroup/metrics".to_string() });
+        let result = PrometheusTarget::parse("process=localhost:9256").unwrap();
+        assert_eq!(result.path, "metrics");
+        assert!(PrometheusTarget::parse("localhost:9256/metrics").is_err());
+        assert!(PrometheusTarget::parse("process=localhost/metrics").is_err());
+        assert!(PrometheusTarget::parse("../process=localhost:9256").is_err());
+        assert!(PrometheusTarget::parse("process node=localhost:9256").is_err());
+        assert_eq!(PrometheusTarget::parse("process-exporter_1=localhost:9256").unwrap().name, "process-exporter_1");
+        assert!(PrometheusTarget::parse_targets(&["cgroup=localhost:9101", "process"]).is_err());
+        assert!(PrometheusTarget::parse_targets(&["cgroup=localhost:9101", "cgroup=localhost:9102"]).is_err());
+        assert_eq!(PrometheusTarget::parse_targets(&["cgroup=localhost:9101", "process=localhost:9256"]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn unit_node_exporter_histogram_quantile() {
+        let buckets = vec![(1.0, 0.0), (2.0, 50.0), (4.0, 100.0), (f64::INFINITY, 100.0)];
+        assert_eq!(histogram_quantile(0.5, &buckets), 2.0);
+        assert_eq!(histogram_quantile(0.25, &buckets), 1.5);
+        assert_eq!(histogram_quantile(0.75, &buckets), 3.0);
+        let buckets = vec![(1.0, 0.0), (f64::INFINITY, 10.0)];
+        assert_eq!(histogram_quantile(0.99, &buckets), 1.0);
+        assert_eq!(histogram_quantile(0.5, &[]), 0.0);
+    }
+
+    #[test]
+    fn unit_node_exporter_group_by_label() {
+        let first_snapshot_time = Local::now();
+        let second_snapshot_time = first_snapshot_time + chrono::Duration::seconds(10);
+        let mut nodeexporterdiff = NodeExporterDiff::new();
+        for (name, label, category, first_value, second_value) in [
+            ("node_disk_reads_completed_total", "_sda", "summary", 100.0, 200.0),
+            ("node_disk_written_bytes_total", "_sda", "summary", 1024.0, 4096.0),
+            ("node_disk_reads_completed_total", "_dm-0", "detail", 10.0, 20.0),
+            ("node_network_receive_bytes_total", "_eth0", "summary", 1.0, 2.0),
+        ] {
+            nodeexporterdiff.btreemapnodeexporterdiff.insert(
+                ("1.1.1.1:9300".to_string(), name.to_string(), label.to_string()),
+                NameCategoryDiff {
+                    first_snapshot_time,
+                    second_snapshot_time,
+                    exporter_type: "counter".to_string(),
+                    category: category.to_string(),
+                    first_value,
+                    second_value,
+                    bucket: None,
+                },
+            );
+        }
+        let result = nodeexporterdiff.group_by_label("node_disk_", &utility::set_regex(&None), &false);
+        assert_eq!(result.len(), 1);
+        let labeldiff = result.get(&("1.1.1.1:9300".to_string(), "sda".to_string())).unwrap();
+        assert_eq!(labeldiff.seconds, 10.0);
+        assert_eq!(labeldiff.differences.get("node_disk_reads_completed_total"), Some(&100.0));
+        assert_eq!(labeldiff.differences.get("node_disk_written_bytes_total"), Some(&3072.0));
+        let result = nodeexporterdiff.group_by_label("node_disk_", &utility::set_regex(&None), &true);
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn unit_node_exporter_restart() {
+        // yb-1 has been rebooted between the snapshots, yb-2 has not.
+        let first_snapshot_time = Local::now();
+        let second_snapshot_time = first_snapshot_time + chrono::Duration::seconds(10);
+        let mut nodeexporterdiff = NodeExporterDiff::new();
+        let mut allnodeexporter = AllNodeExporter::new();
+        for (hostname_port, name, labels, exporter_type, value) in [
+            ("yb-1.local:9300", "node_boot_time_seconds", "", "gauge", 1000.0),
+            ("yb-1.local:9300", "node_disk_reads_completed_total", "_sda", "counter", 100.0),
+            ("yb-2.local:9300", "node_boot_time_seconds", "", "gauge", 1000.0),
+            ("yb-2.local:9300", "node_disk_reads_completed_total", "_sda", "counter", 100.0),
+        ] {
+            allnodeexporter.nodeexporter.push(NodeExporter {
+                hostname_port: hostname_port.to_string(),
+                timestamp: first_snapshot_time,
+                name: name.to_string(),
+                exporter_type: exporter_type.to_string(),
+                labels: labels.to_string(),
+                category: "summary".to_string(),
+                value,
+                ..Default::default()
+            });
+        }
+        nodeexporterdiff.first_snapshot(allnodeexporter);
+        let mut allnodeexporter = AllNodeExporter::new();
+        for (hostname_port, name, labels, exporter_type, value) in [
+            ("yb-1.local:9300", "node_boot_time_seconds", "", "gauge", 2000.0),
+            ("yb-1.local:9300", "node_disk_reads_completed_total", "_sda", "counter", 150.0),
+            ("yb-2.local:9300", "node_boot_time_seconds", "", "gauge", 1000.0),
+            ("yb-2.local:9300", "node_disk_reads_completed_total", "_sda", "counter", 150.0),
+        ] {
+            allnodeexporter.nodeexporter.push(NodeExporter {
+                hostname_port: hostname_port.to_string(),
+                timestamp: second_snapshot_time,
+                name: name.to_string(),
+                exporter_type: exporter_type.to_string(),
+                labels: labels.to_string(),
+                category: "summary".to_string(),
+                value,
+                ..Default::default()
+            });
+        }
+        nodeexporterdiff.second_snapshot(allnodeexporter, &first_snapshot_time);
+        assert_eq!(nodeexporterdiff.restarted_servers.len(), 1);
+        assert!(nodeexporterdiff.restarted_servers.contains("yb-1.local:9300"));
+        // the counters of the rebooted machine started from zero, so the second value is the difference.
+        let result = nodeexporterdiff.group_by_label("node_disk_", &utility::set_regex(&None), &false);
+        assert_eq!(result.get(&("yb-1.local:9300".to_string(), "sda".to_string())).unwrap().differences.get("node_disk_reads_completed_total"), Some(&150.0));
+        assert_eq!(result.get(&("yb-2.local:9300".to_string(), "sda".to_string())).unwrap().differences.get("node_disk_reads_completed_total"), Some(&50.0));
+    }
+
+    #[test]
+    fn unit_node_exporter_summary() {
+        // two snapshots of a host with two CPUs, 10 seconds apart.
+        let first_snapshot = r#"
+# TYPE node_cpu_seconds_total counter
+node_cpu_seconds_total{cpu="0",mode="idle"} 100
+node_cpu_seconds_total{cpu="0",mode="iowait"} 1
+node_cpu_seconds_total{cpu="0",mode="irq"} 0
+node_cpu_seconds_total{cpu="0",mode="nice"} 0
+node_cpu_seconds_total{cpu="0",mode="softirq"} 1
+node_cpu_seconds_total{cpu="0",mode="steal"} 0
+node_cpu_seconds_total{cpu="0",mode="system"} 5
+node_cpu_seconds_total{cpu="0",mode="user"} 10
+node_cpu_seconds_total{cpu="1",mode="idle"} 100
+node_cpu_seconds_total{cpu="1",mode="iowait"} 1
+node_cpu_seconds_total{cpu="1",mode="irq"} 0
+node_cpu_seconds_total{cpu="1",mode="nice"} 0
+node_cpu_seconds_total{cpu="1",mode="softirq"} 1
+node_cpu_seconds_total{cpu="1",mode="steal"} 0
+node_cpu_seconds_total{cpu="1",mode="system"} 5
+node_cpu_seconds_total{cpu="1",mode="user"} 10
+# TYPE node_memory_MemTotal_bytes gauge
+node_memory_MemTotal_bytes 8.589934592e+09
+# TYPE node_memory_MemAvailable_bytes gauge
+node_memory_MemAvailable_bytes 7.516192768e+09
+# TYPE node_memory_Cached_bytes gauge
+node_memory_Cached_bytes 5.36870912e+08
+# TYPE node_memory_Buffers_bytes gauge
+node_memory_Buffers_bytes 1.048576e+08
+# TYPE node_memory_SwapTotal_bytes gauge
+node_memory_SwapTotal_bytes 1.073741824e+09
+# TYPE node_memory_SwapFree_bytes gauge
+node_memory_SwapFree_bytes 1.073741824e+09
+# TYPE node_vmstat_pswpin untyped
+node_vmstat_pswpin 100
+# TYPE node_vmstat_pswpout untyped
+node_vmstat_pswpout 0
+# TYPE node_network_receive_bytes_total counter
+node_network_receive_bytes_total{device="eth0"} 0
+node_network_receive_bytes_total{device="lo"} 1000
+# TYPE node_network_transmit_bytes_total counter
+node_network_transmit_bytes_total{device="eth0"} 0
+node_network_transmit_bytes_total{device="lo"} 1000
+# TYPE node_network_receive_packets_total counter
+node_network_receive_packets_total{device="eth0"} 0
+node_network_receive_packets_total{device="lo"} 10
+# TYPE node_network_transmit_packets_total counter
+node_network_transmit_packets_total{device="eth0"} 0
+node_network_transmit_packets_total{device="lo"} 10
+# TYPE node_network_receive_errs_total counter
+node_network_receive_errs_total{device="eth0"} 0
+node_network_receive_errs_total{device="lo"} 0
+"#;
+        let second_snapshot = r#"
+# TYPE node_cpu_seconds_total counter
+node_cpu_seconds_total{cpu="0",mode="idle"} 140
+node_cpu_seconds_total{cpu="0",mode="iowait"} 3.5
+node_cpu_seconds_total{cpu="0",mode="irq"} 0
+node_cpu_seconds_total{cpu="0",mode="nice"} 0
+node_cpu_seconds_total{cpu="0",mode="softirq"} 1
+node_cpu_seconds_total{cpu="0",mode="steal"} 0
+node_cpu_seconds_total{cpu="0",mode="system"} 7.5
+node_cpu_seconds_total{cpu="0",mode="user"} 15
+node_cpu_seconds_total{cpu="1",mode="idle"} 140
+node_cpu_seconds_total{cpu="1",mode="iowait"} 3.5
+node_cpu_seconds_total{cpu="1",mode="irq"} 0
+node_cpu_seconds_total{cpu="1",mode="nice"} 0
+node_cpu_seconds_total{cpu="1",mode="softirq"} 1
+node_cpu_seconds_total{cpu="1",mode="steal"} 0
+node_cpu_seconds_total{cpu="1",mode="system"} 7.5
+node_cpu_seconds_total{cpu="1",mode="user"} 15
+# TYPE node_memory_MemTotal_bytes gauge
+node_memory_MemTotal_bytes 8.589934592e+09
+# TYPE node_memory_MemAvailable_bytes gauge
+node_memory_MemAvailable_bytes 6.442450944e+09
+# TYPE node_memory_Cached_bytes gauge
+node_memory_Cached_bytes 1.073741824e+09
+# TYPE node_memory_Buffers_bytes gauge
+node_memory_Buffers_bytes 1.048576e+08
+# TYPE node_memory_SwapTotal_bytes gauge
+node_memory_SwapTotal_bytes 1.073741824e+09
+# TYPE node_memory_SwapFree_bytes gauge
+node_memory_SwapFree_bytes 1.021313024e+09
+# TYPE node_vmstat_pswpin untyped
+node_vmstat_pswpin 200
+# TYPE node_vmstat_pswpout untyped
+node_vmstat_pswpout 50
+# TYPE node_network_receive_bytes_total counter
+node_network_receive_bytes_total{device="eth0"} 102400
+node_network_receive_bytes_total{device="lo"} 1000
+# TYPE node_network_transmit_bytes_total counter
+node_network_transmit_bytes_total{device="eth0"} 20480
+node_network_transmit_bytes_total{device="lo"} 1000
+# TYPE node_network_receive_packets_total counter
+node_network_receive_packets_total{device="eth0"} 1000
+node_network_receive_packets_total{device="lo"} 10
+# TYPE node_network_transmit_packets_total counter
+node_network_transmit_packets_total{device="eth0"} 500
+node_network_transmit_packets_total{device="lo"} 10
+# TYPE node_network_receive_errs_total counter
+node_network_receive_errs_total{device="eth0"} 10
+node_network_receive_errs_total{device="lo"} 0
+"#;
+        let first_snapshot_time = Local::now();
+        let second_snapshot_time = first_snapshot_time + chrono::Duration::seconds(10);
+        let mut nodeexporterdiff = NodeExporterDiff::new();
+        for (snapshot, timestamp) in [(first_snapshot, first_snapshot_time), (second_snapshot, second_snapshot_time)]
+        {
+            let mut allnodeexporter = AllNodeExporter::new();
+            allnodeexporter.nodeexporter = AllNodeExporter::parse_nodeexporter(snapshot.to_string());
+            allnodeexporter.nodeexporter.iter_mut().for_each(|r| r.timestamp = timestamp);
+            allnodeexporter.nodeexporter.iter_mut().for_each(|r| r.hostname_port = "1.1.1.1:9300".to_string());
+            if timestamp == first_snapshot_time {
+                nodeexporterdiff.first_snapshot(allnodeexporter);
+            } else {
+                nodeexporterdiff.second_snapshot(allnodeexporter, &first_snapshot_time);
+            }
+        }
+        let hostname_filter = utility::set_regex(&None);
+
+        // 100 seconds of CPU time in total: idle 80, user 10, system 5, iowait 5.
+        let cpu = nodeexporterdiff.cpu_percentages(&hostname_filter);
+        let modes = cpu.get("1.1.1.1:9300").unwrap();
+        assert_eq!(modes.get("idle"), Some(&80.0));
+        assert_eq!(modes.get("user"), Some(&10.0));
+        assert_eq!(modes.get("system"), Some(&5.0));
+        assert_eq!(modes.get("iowait"), Some(&5.0));
+        assert_eq!(modes.get("steal"), Some(&0.0));
+        assert_eq!(modes.values().sum::<f64>(), 100.0);
+
+        // the memory is taken from the second snapshot, the swap activity is per second.
+        let memory = nodeexporterdiff.memory_summary(&hostname_filter);
+        assert_eq!(memory.get("1.1.1.1:9300"), Some(&MemorySummary {
+            total_mb: 8192.0,
+            used_mb: 2048.0,
+            cached_mb: 1124.0,
+            available_mb: 6144.0,
+            swap_used_mb: 50.0,
+            swap_in_per_second: 10.0,
+            swap_out_per_second: 5.0,
+        }));
+
+        // lo has no traffic between the snapshots, and is not included.
+        let network = nodeexporterdiff.network_summary(&hostname_filter, &false);
+        assert_eq!(network.len(), 1);
+        assert_eq!(network.get(&("1.1.1.1:9300".to_string(), "eth0".to_string())), Some(&NetworkSummary {
+            receive_kb_per_second: 10.0,
+            transmit_kb_per_second: 2.0,
+            receive_packets_per_second: 100.0,
+            transmit_packets_per_second: 50.0,
+            receive_errors_per_second: 1.0,
+            transmit_errors_per_second: 0.0,
+        }));
+
+        // a hostname filter that doesn't match the host returns nothing.
+        let hostname_filter = utility::set_regex(&Some("2.2.2.2".to_string()));
+        assert!(nodeexporterdiff.cpu_percentages(&hostname_filter).is_empty());
+        assert!(nodeexporterdiff.memory_summary(&hostname_filter).is_empty());
     }
 
     #[tokio::test]
@@ -1,9 +1,9 @@
 //! The structs
 //!
 use chrono::{DateTime, Local, Utc};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct NodeExporter {
     /// yb_stats added to allow understanding the source host.
     pub hostname_port: String,
@@ -13,10 +13,79 @@ pub struct NodeExporter {
     pub exporter_type: String,
     pub labels: String,
     pub category: String,
+    #[serde(with = "prometheus_float")]
     pub value: f64,
     pub exporter_timestamp: DateTime<Utc>,
+    /// The upper bound ('le') of a histogram bucket, or the quantile of a summary.
+    /// The labels are suffixed with `_le_<bound>` or `_quantile_<quantile>` to make each row unique.
+    #[serde(default, with = "prometheus_float::option")]
+    pub bucket: Option<f64>,
 }
-#[derive(Debug, Default)]
+/// Serialization of prometheus values, which can be `+Inf`, `-Inf` or `NaN`.
+///
+/// JSON has no representation for non-finite numbers, and serde_json writes them as null,
+/// which turns the `+Inf` bucket into None, and makes a `NaN` summary quantile unreadable.
+/// Therefore, non-finite numbers are written as a string in the prometheus notation.
+///
+/// Older yb_stats versions expect a number, and cannot read snapshots with these strings.
+mod prometheus_float {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Float {
+        Number(f64),
+        Text(String),
+    }
+
+    impl Float {
+        fn to_f64<E: Error>(&self) -> Result<f64, E> {
+            match self {
+                Float::Number(number) => Ok(*number),
+                Float::Text(text) => match text.as_str() {
+                    "+Inf" | "Inf" => Ok(f64::INFINITY),
+                    "-Inf" => Ok(f64::NEG_INFINITY),
+                    "NaN" => Ok(f64::NAN),
+                    _ => Err(E::custom(format!("invalid prometheus value: {}", text))),
+                },
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_nan() {
+            serializer.serialize_str("NaN")
+        } else if value.is_infinite() {
+            serializer.serialize_str(if value.is_sign_positive() { "+Inf" } else { "-Inf" })
+        } else {
+            serializer.serialize_f64(*value)
+        }
+    }
+
+    /// A null value is written by versions that didn't serialize non-finite numbers, and is read as `NaN`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Option::<Float>::deserialize(deserializer)?
+            .map_or(Ok(f64::NAN), |float| float.to_f64())
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+            Option::<Float>::deserialize(deserializer)?
+                .map(|float| float.to_f64())
+                .transpose()
+        }
+    }
+}
+#[derive(Debug, Default, Clone)]
 pub struct AllNodeExporter {
     pub nodeexporter: Vec<NodeExporter>,
 }
@@ -29,6 +98,7 @@ pub struct NameCategoryDiff {
     pub category: String,
     pub first_value: f64,
     pub second_value: f64,
+    pub bucket: Option<f64>,
 }
 
 // (String, String, String) = (hostname_port, name, labels)
@@ -37,4 +107,57 @@ type BTreeNodeExporterDiff = BTreeMap<(String, String, String), NameCategoryDiff
 #[derive(Default)]
 pub struct NodeExporterDiff {
     pub btreemapnodeexporterdiff: BTreeNodeExporterDiff,
-}
\ No newline at end of file
+    /// The hostname_port of the machines for which node_boot_time_seconds changed, meaning the machine has been rebooted.
+    pub restarted_servers: BTreeSet<String>,
+}
+/// The differences of the statistics of a single label, such as a disk or a network interface, of a host.
+///
+/// This is used for the derived views, such as the iostat view.
+#[derive(Debug, Default)]
+pub struct LabelDiff {
+    /// The number of seconds between the first and the second snapshot.
+    pub seconds: f64,
+    /// statistic name -> difference between the first and second snapshot.
+    pub differences: BTreeMap<String, f64>,
+}
+/// The memory usage of a host at the time of the second snapshot, and the swap activity between the snapshots.
+#[derive(Debug, Default, PartialEq)]
+pub struct MemorySummary {
+    pub total_mb: f64,
+    pub used_mb: f64,
+    pub cached_mb: f64,
+    pub available_mb: f64,
+    pub swap_used_mb: f64,
+    /// Pages swapped in per second.
+    pub swap_in_per_second: f64,
+    /// Pages swapped out per second.
+    pub swap_out_per_second: f64,
+}
+/// The traffic per second of a network interface of a host between the snapshots.
+#[derive(Debug, Default, PartialEq)]
+pub struct NetworkSummary {
+    pub receive_kb_per_second: f64,
+    pub transmit_kb_per_second: f64,
+    pub receive_packets_per_second: f64,
+    pub transmit_packets_per_second: f64,
+    pub receive_errors_per_second: f64,
+    pub transmit_errors_per_second: f64,
+}
+/// The bucket differences of a histogram, or the quantile values of a summary, of a host.
+#[derive(Debug, Default)]
+pub struct BucketDiff {
+    pub exporter_type: String,
+    /// (upper bound or quantile, difference for a histogram or second value for a summary), ordered by the bound.
+    pub buckets: Vec<(f64, f64)>,
+}
+/// An additional prometheus endpoint, set with `--prometheus-targets` as `name=host:port/path`.
+///
+/// The name is used for the snapshot file: `prometheus_<name>.json`.
+/// If the path is omitted, `metrics` is used.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PrometheusTarget {
+    pub name: String,
+    pub host: String,
+    pub port: String,
+    pub path: String,
+}
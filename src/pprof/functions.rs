@@ -1,9 +1,13 @@
 //! The impls and functions.
 //!
-use std::{fs, io::Write, sync::mpsc::channel, time::Instant, env};
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, fs, io::Write, sync::{mpsc::channel, LazyLock}, time::{Duration, Instant}};
+use regex::Regex;
 use log::*;
-use anyhow::{Result, Context};
+use anyhow::{Result, Context, bail};
+use crate::snapshot;
 use crate::utility;
+use crate::pprof::{HeapProfileStack, AllHeapProfileStacks, HeapProfileDiff, CpuProfile, CpuProfileSample, CpuProfileStack, AllCpuProfileStacks};
+use crate::Opts;
 
 pub struct Pprof;
 
@@ -43,7 +47,13 @@ impl Pprof {
                     let tx = tx.clone();
                     s.spawn(move |_| {
                         let pprof_data = Pprof::read_http(host, port);
-                        tx.send((format!("{}:{}", host, port), pprof_data)).expect("error sending data via tx (pprof)");
+                        // only servers that return a heap profile are symbolised.
+                        let symbols_data = if pprof_data.starts_with("heap profile") {
+                            Pprof::read_http_symbols(host, port, &heap_profile_addresses(&pprof_data))
+                        } else {
+                            String::new()
+                        };
+                        tx.send((format!("{}:{}", host, port), pprof_data, symbols_data)).expect("error sending data via tx (pprof)");
                     });
                 }
             }
@@ -51,22 +61,29 @@ impl Pprof {
 
         info!("end parallel http read {:?}", timer.elapsed());
 
-        for (hostname_port, pprof_data) in rx {
+        for (hostname_port, pprof_data, symbols_data) in rx {
             if pprof_data.starts_with("heap profile")
                 && snapshot_number >= 0
             {
-                let current_directory = env::current_dir()?;
-                let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
+                let current_snapshot_directory = snapshot::snapshot_directory(&snapshot_number.to_string())?;
 
                 let pprof_file = &current_snapshot_directory.join(format!("pprof_growth_{}.txt", hostname_port));
                 let mut file = fs::OpenOptions::new()
                     .create(true)
                     .write(true)
+                    .truncate(true)
                     .open(pprof_file)
                     .with_context(|| format!("Cannot create file: {}", pprof_file.display()))?;
 
                 file.write_all(pprof_data.as_bytes())
                     .with_context(|| format!("Error writing file: {}", pprof_file.display()))?;
+
+                if !symbols_data.is_empty()
+                {
+                    let symbols_file = &current_snapshot_directory.join(format!("pprof_growth_symbols_{}.txt", hostname_port));
+                    fs::write(symbols_file, symbols_data.as_bytes())
+                        .with_context(|| format!("Error writing file: {}", symbols_file.display()))?;
+                }
             };
         }
         Ok(())
@@ -78,11 +95,707 @@ impl Pprof {
     {
         utility::http_get(host, port, "pprof/growth")
     }
+    pub async fn perform_cpu_profile_snapshot(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        snapshot_number: i32,
+        parallel: usize,
+        seconds: u64,
+    ) -> Result<()>
+    {
+        info!("begin cpu profile snapshot");
+        let timer = Instant::now();
+
+        Pprof::read_and_write_cpu_profile(hosts, ports, snapshot_number, parallel, seconds).await?;
+
+        info!("end cpu profile snapshot: {:?}", timer.elapsed());
+
+        Ok(())
+    }
+    /// Take a CPU profile of the given number of seconds on all servers in parallel,
+    /// and save the profile and the symbols of its program counters in the snapshot.
+    pub async fn read_and_write_cpu_profile(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        snapshot_number: i32,
+        parallel: usize,
+        seconds: u64,
+    ) -> Result<()>
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel).build().unwrap();
+        let (tx, rx) = channel();
+
+        pool.scope(move |s| {
+            for host in hosts {
+                for port in ports {
+                    let tx = tx.clone();
+                    s.spawn(move |_| {
+                        let profile_data = Pprof::read_http_cpu_profile(host, port, seconds);
+                        // only servers that return a valid profile are symbolised, the others return None.
+                        let symbols_data = parse_cpu_profile(&profile_data)
+                            .ok()
+                            .map(|profile| Pprof::read_http_symbols(host, port, &lookup_addresses(&profile)));
+                        tx.send((format!("{}:{}", host, port), profile_data, symbols_data)).expect("error sending data via tx (pprof cpu)");
+                    });
+                }
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        for (hostname_port, profile_data, symbols_data) in rx {
+            if let Some(symbols_data) = symbols_data.filter(|_| snapshot_number >= 0)
+            {
+                let current_snapshot_directory = snapshot::snapshot_directory(&snapshot_number.to_string())?;
+
+                // the profile is saved even if the server could not symbolise it, then the frames are shown as addresses.
+                let mut files = vec![(format!("pprof_cpu_{}.prof", hostname_port), profile_data.as_slice())];
+                if !symbols_data.is_empty()
+                {
+                    files.push((format!("pprof_cpu_symbols_{}.txt", hostname_port), symbols_data.as_bytes()));
+                }
+                for (filename, data) in files
+                {
+                    let pprof_file = &current_snapshot_directory.join(filename);
+                    let mut file = fs::OpenOptions::new()
+                        .create(true)
+                        .write(true)
+                        .truncate(true)
+                        .open(pprof_file)
+                        .with_context(|| format!("Cannot create file: {}", pprof_file.display()))?;
+
+                    file.write_all(data)
+                        .with_context(|| format!("Error writing file: {}", pprof_file.display()))?;
+                }
+            };
+        }
+        Ok(())
+    }
+    /// The CPU profile request returns after the profiling time, so the timeout must be longer than that.
+    fn read_http_cpu_profile(
+        host: &str,
+        port: &str,
+        seconds: u64,
+    ) -> Vec<u8>
+    {
+        utility::http_get_bytes(host, port, &format!("pprof/profile?seconds={}", seconds), Duration::from_secs(seconds + 30))
+    }
+    /// Ask the server to symbolise the addresses of a profile.
+    /// The server returns a line with the address and the function name per address: `0x0000000002f9c1d5\tyb::Foo()`.
+    fn read_http_symbols(
+        host: &str,
+        port: &str,
+        addresses: &BTreeSet<u64>,
+    ) -> String
+    {
+        let addresses = addresses
+            .iter()
+            .map(|address| format!("{:#x}", address))
+            .collect::<Vec<String>>()
+            .join("+");
+        utility::http_post(host, port, "pprof/symbol", addresses)
+    }
+}
+
+/// Parse a gperftools (legacy) binary CPU profile.
+///
+/// The profile consists of machine words (64 bits for the servers yb_stats reads), in little endian:
+/// - the header: 0, 3 (the number of header words that follow), 0 (the version), the sampling period in microseconds, 0.
+/// - the samples: the sample count, the number of program counters, and the program counters.
+/// - the trailer: 0, 1, 0.
+///
+/// After the trailer, the memory maps of the process follow as text, which are not used.
+pub fn parse_cpu_profile(
+    profile_data: &[u8],
+) -> Result<CpuProfile>
+{
+    let mut words = profile_data
+        .chunks_exact(8)
+        .map(|word| u64::from_le_bytes(word.try_into().unwrap()));
+    let header: Vec<u64> = words.by_ref().take(5).collect();
+    if header.len() != 5 || header[0] != 0 || header[1] != 3
+    {
+        bail!("Not a CPU profile: invalid header");
+    }
+    let mut profile = CpuProfile { period_microseconds: header[3], ..Default::default() };
+    loop
+    {
+        let (Some(count), Some(depth)) = (words.next(), words.next()) else {
+            bail!("Not a CPU profile: missing trailer");
+        };
+        let addresses: Vec<u64> = words.by_ref().take(depth as usize).collect();
+        if addresses.len() != depth as usize
+        {
+            bail!("Not a CPU profile: truncated sample");
+        }
+        // the trailer is a sample with count 0 and a single program counter 0.
+        if count == 0 && depth == 1 && addresses[0] == 0
+        {
+            break;
+        }
+        profile.samples.push(CpuProfileSample { count, addresses });
+    }
+    Ok(profile)
+}
+
+/// Return the address to symbolise for the program counter at the given position in a sample.
+/// Except for the innermost frame, the program counters are return addresses, which point to the instruction after the call.
+/// That instruction can belong to a different function or line, so the address before it is used.
+fn lookup_address(
+    position: usize,
+    address: u64,
+) -> u64
+{
+    if position == 0 { address } else { address.saturating_sub(1) }
+}
+
+/// The unique addresses to symbolise for a CPU profile.
+fn lookup_addresses(profile: &CpuProfile) -> BTreeSet<u64>
+{
+    profile.samples
+        .iter()
+        .flat_map(|sample| sample.addresses.iter().enumerate().map(|(position, address)| lookup_address(position, *address)))
+        .collect()
+}
+
+/// Parse the symbols returned by `/pprof/symbol` into a map of address and function name.
+fn parse_symbols(symbols_data: &str) -> HashMap<u64, String>
+{
+    symbols_data
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .filter_map(|(address, function)| parse_address(address).map(|address| (address, function.trim().to_string())))
+        .collect()
+}
+
+/// Turn the samples of a CPU profile into stacks of function names, using the address if no symbol was found.
+pub fn symbolise_cpu_profile(
+    hostname_port: &str,
+    profile: &CpuProfile,
+    symbols: &HashMap<u64, String>,
+) -> Vec<CpuProfileStack>
+{
+    profile.samples
+        .iter()
+        .map(|sample| CpuProfileStack {
+            hostname_port: hostname_port.to_string(),
+            samples: sample.count,
+            seconds: sample.count.saturating_mul(profile.period_microseconds) as f64 / 1_000_000_f64,
+            frames: sample.addresses
+                .iter()
+                .enumerate()
+                .map(|(position, address)| {
+                    let address = lookup_address(position, *address);
+                    symbols.get(&address).cloned().unwrap_or_else(|| format!("{:#x}", address))
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Return the captures of the stack lines of a heap profile, which are the lines between the first line and the mapped libraries.
+fn heap_profile_stack_lines(pprof_data: &str) -> impl Iterator<Item = regex::Captures<'_>>
+{
+    // the in-use count and bytes, the total count and bytes, and the frames.
+    static HEAP_PROFILE_STACK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\d+):\s*(\d+)\s*\[\s*\d+:\s*\d+\]\s*@\s*(.*)$").unwrap());
+    pprof_data
+        .lines()
+        // the first line is the total of the profile, not a stack.
+        .skip_while(|line| !line.starts_with("heap profile:"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("MAPPED_LIBRARIES:"))
+        .filter_map(|line| HEAP_PROFILE_STACK.captures(line))
+}
+
+/// The unique addresses to symbolise for a heap profile.
+/// Like the program counters of a CPU profile, the frames other than the innermost are return addresses.
+fn heap_profile_addresses(pprof_data: &str) -> BTreeSet<u64>
+{
+    heap_profile_stack_lines(pprof_data)
+        .flat_map(|captures| captures[3]
+            .split_whitespace()
+            .enumerate()
+            .filter_map(|(position, frame)| parse_address(frame).map(|address| lookup_address(position, address)))
+            .collect::<Vec<u64>>())
+        .collect()
+}
+
+/// Parse a gperftools heap profile in the text format into allocation stacks.
+///
+/// The heap profile looks like this:
+/// ```text
+/// heap profile:   67: 49971088 [    67: 49971088] @ growthz
+///      1:  8388608 [     1:  8388608] @ 0x2f9c1d5 0x2f7f0e5 0x2f7e4b1
+///      4:  4194304 [     4:  4194304] @ 0x2f9c1d5 0x2a3c5e6
+///
+/// MAPPED_LIBRARIES:
+/// ```
+/// The first count and bytes of a stack are the in-use allocations, the frames are innermost first.
+/// The profile contains addresses only: the frames are replaced with the function names in symbols,
+/// which are read from `/pprof/symbol` when the profile is taken. A frame without a symbol keeps its address.
+pub fn parse_heap_profile(
+    hostname_port: &str,
+    pprof_data: &str,
+    symbols: &HashMap<u64, String>,
+) -> Vec<HeapProfileStack>
+{
+    heap_profile_stack_lines(pprof_data)
+        .map(|captures| HeapProfileStack {
+            hostname_port: hostname_port.to_string(),
+            count: captures[1].parse().unwrap_or_default(),
+            bytes: captures[2].parse().unwrap_or_default(),
+            frames: captures[3]
+                .split_whitespace()
+                .enumerate()
+                .map(|(position, frame)| parse_address(frame)
+                    .and_then(|address| symbols.get(&lookup_address(position, address)).cloned())
+                    .unwrap_or_else(|| frame.to_string()))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Parse a hexadecimal address, such as "0x2f9c1d5", into a number, so differences in leading zeros do not matter.
+fn parse_address(address: &str) -> Option<u64>
+{
+    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+/// Print the frames of a stack indented below the stack line.
+fn print_frames(frames: &[String])
+{
+    for frame in frames
+    {
+        println!("  {}", frame);
+    }
+}
+
+impl AllHeapProfileStacks {
+    pub fn new() -> Self { Default::default() }
+    pub async fn read_pprof(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> AllHeapProfileStacks
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel).build().unwrap();
+        let (tx, rx) = channel();
+
+        pool.scope(move |s| {
+            for host in hosts {
+                for port in ports {
+                    let tx = tx.clone();
+                    s.spawn(move |_| {
+                        let pprof_data = Pprof::read_http(host, port);
+                        let symbols = if pprof_data.starts_with("heap profile") {
+                            parse_symbols(&Pprof::read_http_symbols(host, port, &heap_profile_addresses(&pprof_data)))
+                        } else {
+                            HashMap::new()
+                        };
+                        let stacks = parse_heap_profile(&format!("{}:{}", host, port), &pprof_data, &symbols);
+                        tx.send(stacks).expect("error sending data via tx (pprof)");
+                    });
+                }
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        let mut allheapprofilestacks = AllHeapProfileStacks::new();
+        allheapprofilestacks.stacks = rx.iter().flatten().collect();
+        allheapprofilestacks
+    }
+    /// Read the heap profiles of a snapshot and their symbols, and parse them into allocation stacks.
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<AllHeapProfileStacks>
+    {
+        let snapshot_directory = snapshot::snapshot_directory(snapshot_number)?;
+        let mut allheapprofilestacks = AllHeapProfileStacks::new();
+        for entry in fs::read_dir(&snapshot_directory)
+            .with_context(|| format!("Error reading snapshot directory: {}", snapshot_directory.display()))?
+            .filter_map(|entry| entry.ok())
+        {
+            let filename = entry.file_name().into_string().unwrap_or_default();
+            if let Some(hostname_port) = filename.strip_prefix("pprof_growth_")
+                .filter(|_| !filename.starts_with("pprof_growth_symbols_"))
+                .and_then(|filename| filename.strip_suffix(".txt"))
+            {
+                let pprof_data = fs::read_to_string(entry.path())
+                    .with_context(|| format!("Error reading file: {}", entry.path().display()))?;
+                let symbols_file = snapshot_directory.join(format!("pprof_growth_symbols_{}.txt", hostname_port));
+                let symbols = parse_symbols(&fs::read_to_string(&symbols_file).unwrap_or_default());
+                allheapprofilestacks.stacks.extend(parse_heap_profile(hostname_port, &pprof_data, &symbols));
+            }
+        }
+        Ok(allheapprofilestacks)
+    }
+    /// Return the top_n allocation stacks by bytes.
+    pub fn top_stacks(
+        &self,
+        hostname_filter: &Regex,
+        top_n: usize,
+    ) -> Vec<&HeapProfileStack>
+    {
+        let mut stacks: Vec<&HeapProfileStack> = self.stacks
+            .iter()
+            .filter(|stack| hostname_filter.is_match(&stack.hostname_port))
+            .collect();
+        stacks.sort_by_key(|stack| std::cmp::Reverse(stack.bytes));
+        stacks.truncate(top_n);
+        stacks
+    }
+    /// Print the top_n allocation stacks by bytes, with the frames of the stack.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        top_n: usize,
+    )
+    {
+        for stack in self.top_stacks(hostname_filter, top_n)
+        {
+            println!("{:20} allocations: {:10} bytes: {:15}", stack.hostname_port, stack.count, stack.bytes);
+            print_frames(&stack.frames);
+        }
+    }
+}
+
+impl HeapProfileDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<HeapProfileDiff>
+    {
+        let mut heapprofilediff = HeapProfileDiff::new();
+        heapprofilediff.first_snapshot(AllHeapProfileStacks::read_snapshot(begin_snapshot)?);
+        heapprofilediff.second_snapshot(AllHeapProfileStacks::read_snapshot(end_snapshot)?);
+        Ok(heapprofilediff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allheapprofilestacks: AllHeapProfileStacks,
+    )
+    {
+        for stack in allheapprofilestacks.stacks
+        {
+            let heapprofiledifffields = self.btreeheapprofilediff.entry((stack.hostname_port, stack.frames)).or_default();
+            heapprofiledifffields.first_count += stack.count;
+            heapprofiledifffields.first_bytes += stack.bytes;
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allheapprofilestacks: AllHeapProfileStacks,
+    )
+    {
+        for stack in allheapprofilestacks.stacks
+        {
+            let heapprofiledifffields = self.btreeheapprofilediff.entry((stack.hostname_port, stack.frames)).or_default();
+            heapprofiledifffields.second_count += stack.count;
+            heapprofiledifffields.second_bytes += stack.bytes;
+        }
+    }
+    /// Print the top_n stacks that grew most in bytes between the snapshots, with the frames of the stack.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        top_n: usize,
+    )
+    {
+        let mut stacks: Vec<_> = self.btreeheapprofilediff
+            .iter()
+            .filter(|((hostname_port, _), row)| hostname_filter.is_match(hostname_port) && row.second_bytes > row.first_bytes)
+            .collect();
+        stacks.sort_by_key(|(_, row)| std::cmp::Reverse(row.second_bytes - row.first_bytes));
+        for ((hostname_port, frames), row) in stacks.into_iter().take(top_n)
+        {
+            println!("{:20} allocations: {:10} {:+10} bytes: {:15} {:+15}",
+                     hostname_port,
+                     row.second_count,
+                     row.second_count - row.first_count,
+                     row.second_bytes,
+                     row.second_bytes - row.first_bytes,
+            );
+            print_frames(frames);
+        }
+    }
+}
+
+/// The CPU time of a function in a CPU profile.
+#[derive(Debug, Default, PartialEq)]
+struct FunctionTime {
+    /// The time the function itself was running.
+    flat_seconds: f64,
+    /// The time the function itself or one of the functions it called was running.
+    cumulative_seconds: f64,
+}
+
+impl AllCpuProfileStacks {
+    pub fn new() -> Self { Default::default() }
+    /// Read the CPU profiles of a snapshot and their symbols, and turn them into stacks of function names.
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<AllCpuProfileStacks>
+    {
+        let snapshot_directory = snapshot::snapshot_directory(snapshot_number)?;
+        let mut allcpuprofilestacks = AllCpuProfileStacks::new();
+        for entry in fs::read_dir(&snapshot_directory)
+            .with_context(|| format!("Error reading snapshot directory: {}", snapshot_directory.display()))?
+            .filter_map(|entry| entry.ok())
+        {
+            let filename = entry.file_name().into_string().unwrap_or_default();
+            if let Some(hostname_port) = filename.strip_prefix("pprof_cpu_").and_then(|filename| filename.strip_suffix(".prof"))
+            {
+                let profile_data = fs::read(entry.path())
+                    .with_context(|| format!("Error reading file: {}", entry.path().display()))?;
+                let profile = parse_cpu_profile(&profile_data)
+                    .with_context(|| format!("Error parsing file: {}", entry.path().display()))?;
+                let symbols_file = snapshot_directory.join(format!("pprof_cpu_symbols_{}.txt", hostname_port));
+                let symbols = parse_symbols(&fs::read_to_string(&symbols_file).unwrap_or_default());
+                allcpuprofilestacks.stacks.extend(symbolise_cpu_profile(hostname_port, &profile, &symbols));
+            }
+        }
+        allcpuprofilestacks.stacks.sort_by(|a, b| a.hostname_port.cmp(&b.hostname_port));
+        Ok(allcpuprofilestacks)
+    }
+    /// Calculate the flat and cumulative time per host and function.
+    /// A function that occurs more than once in a stack because of recursion is counted once for the cumulative time.
+    fn function_times(
+        &self,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<String, BTreeMap<String, FunctionTime>>
+    {
+        let mut function_times: BTreeMap<String, BTreeMap<String, FunctionTime>> = BTreeMap::new();
+        for stack in self.stacks.iter().filter(|stack| hostname_filter.is_match(&stack.hostname_port))
+        {
+            let host_function_times = function_times.entry(stack.hostname_port.clone()).or_default();
+            if let Some(function) = stack.frames.first()
+            {
+                host_function_times.entry(function.clone()).or_default().flat_seconds += stack.seconds;
+            }
+            for function in stack.frames.iter().collect::<BTreeSet<_>>()
+            {
+                host_function_times.entry(function.clone()).or_default().cumulative_seconds += stack.seconds;
+            }
+        }
+        function_times
+    }
+    /// Print the top_n functions per host by flat time, in the same way as `pprof -top`.
+    pub fn print_top(
+        &self,
+        hostname_filter: &Regex,
+        top_n: usize,
+    )
+    {
+        for (hostname_port, function_times) in self.function_times(hostname_filter)
+        {
+            let total_seconds: f64 = self.stacks
+                .iter()
+                .filter(|stack| stack.hostname_port == hostname_port)
+                .map(|stack| stack.seconds)
+                .sum();
+            println!("{}", "-".repeat(140));
+            println!("Host: {}, total: {:.3} seconds", hostname_port, total_seconds);
+            println!("{}", "-".repeat(140));
+            println!("{:>10} {:>7} {:>10} {:>7} function", "flat_s", "flat%", "cum_s", "cum%");
+            let mut function_times: Vec<_> = function_times.into_iter().collect();
+            function_times.sort_by(|(_, a), (_, b)| b.flat_seconds.total_cmp(&a.flat_seconds).then(b.cumulative_seconds.total_cmp(&a.cumulative_seconds)));
+            for (function, function_time) in function_times.into_iter().take(top_n)
+            {
+                println!("{:10.3} {:7.2} {:10.3} {:7.2} {}",
+                         function_time.flat_seconds,
+                         if total_seconds == 0.0 { 0.0 } else { function_time.flat_seconds / total_seconds * 100_f64 },
+                         function_time.cumulative_seconds,
+                         if total_seconds == 0.0 { 0.0 } else { function_time.cumulative_seconds / total_seconds * 100_f64 },
+                         function,
+                );
+            }
+        }
+    }
+    /// Return the stacks in folded stack format: the frames outermost first separated by ";", with the number of samples.
+    /// Identical stacks of different hosts are added up.
+    pub fn folded_stacks(
+        &self,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<String, u64>
+    {
+        let mut folded_stacks: BTreeMap<String, u64> = BTreeMap::new();
+        for stack in self.stacks.iter().filter(|stack| hostname_filter.is_match(&stack.hostname_port))
+        {
+            let folded_stack = stack.frames.iter().rev().cloned().collect::<Vec<String>>().join(";");
+            *folded_stacks.entry(folded_stack).or_default() += stack.samples;
+        }
+        folded_stacks
+    }
+}
+
+/// Print the top functions of the CPU profiles of a snapshot, or the stacks in folded stack format with `--cpu-profile-folded`.
+pub async fn print_cpu_profile(
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let allcpuprofilestacks = AllCpuProfileStacks::read_snapshot(options.print_cpu_profile.as_ref().unwrap())?;
+    if options.cpu_profile_folded
+    {
+        for (folded_stack, samples) in allcpuprofilestacks.folded_stacks(&hostname_filter)
+        {
+            println!("{} {}", folded_stack, samples);
+        }
+    }
+    else
+    {
+        allcpuprofilestacks.print_top(&hostname_filter, options.pprof_top);
+    }
+    Ok(())
+}
+
+pub async fn print_pprof(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let allheapprofilestacks = match options.print_pprof.as_ref().unwrap() {
+        Some(snapshot_number) => AllHeapProfileStacks::read_snapshot(snapshot_number)?,
+        None => AllHeapProfileStacks::read_pprof(&hosts, &ports, parallel).await,
+    };
+    allheapprofilestacks.print(&hostname_filter, options.pprof_top);
+    Ok(())
+}
+
+pub async fn pprof_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, _) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let pprof_diff = HeapProfileDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    pprof_diff.print(&hostname_filter, options.pprof_top);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pprof::HeapProfileDiffFields;
+
+    #[test]
+    fn unit_parse_heap_profile() {
+        // This is what /pprof/growth returns.
+        let pprof = r#"heap profile:    5: 12582912 [     5: 12582912] @ growthz
+     1:  8388608 [     1:  8388608] @ 0x0000000002f9c1d5 0x0000000002f7f0e5 0x0000000002f7e4b1
+     4:  4194304 [     4:  4194304] @ 0x0000000002f9c1d5 0x0000000002a3c5e6
+
+MAPPED_LIBRARIES:
+00400000-03d5e000 r-xp 00000000 fd:00 123 /home/yugabyte/bin/yb-tserver
+"#;
+        // the frames after the innermost frame are symbolised with the address before the return address.
+        assert_eq!(heap_profile_addresses(pprof), BTreeSet::from([0x2f9c1d5, 0x2f7f0e4, 0x2f7e4b0, 0x2a3c5e5]));
+        // This is what /pprof/symbol returns for these addresses, the addresses it cannot symbolise are not returned.
+        let symbols = parse_symbols("0x0000000002f9c1d5\ttcmalloc::allocate_full_cpp_throw_oom()\n0x0000000002a3c5e5\tyb::rpc::GrowableBuffer::Expand()\n");
+        let stacks = parse_heap_profile("yb-1.local:9000", pprof, &symbols);
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(stacks[0].count, 1);
+        assert_eq!(stacks[0].bytes, 8388608);
+        assert_eq!(stacks[0].frames, vec!["tcmalloc::allocate_full_cpp_throw_oom()", "0x0000000002f7f0e5", "0x0000000002f7e4b1"]);
+        assert_eq!(stacks[1].frames, vec!["tcmalloc::allocate_full_cpp_throw_oom()", "yb::rpc::GrowableBuffer::Expand()"]);
+        // without symbols the frames are the addresses.
+        assert_eq!(parse_heap_profile("yb-1.local:9000", pprof, &HashMap::new())[1].frames, vec!["0x0000000002f9c1d5", "0x0000000002a3c5e6"]);
+
+        let allheapprofilestacks = AllHeapProfileStacks { stacks };
+        let top_stacks = allheapprofilestacks.top_stacks(&utility::set_regex(&None), 1);
+        assert_eq!(top_stacks.len(), 1);
+        assert_eq!(top_stacks[0].bytes, 8388608);
+    }
+
+    /// Create a binary CPU profile with a sampling period of 10000 microseconds.
+    fn cpu_profile_data(samples: &[(u64, &[u64])]) -> Vec<u8>
+    {
+        let mut words: Vec<u64> = vec![0, 3, 0, 10000, 0];
+        for (count, addresses) in samples
+        {
+            words.push(*count);
+            words.push(addresses.len() as u64);
+            words.extend(addresses.iter());
+        }
+        words.extend([0, 1, 0]);
+        let mut profile_data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        profile_data.extend(b"00400000-03d5e000 r-xp 00000000 fd:00 123 /home/yugabyte/bin/yb-tserver\n");
+        profile_data
+    }
+
+    #[test]
+    fn unit_parse_cpu_profile() {
+        let profile = parse_cpu_profile(&cpu_profile_data(&[(3, &[0x100, 0x201]), (1, &[0x300])])).unwrap();
+        assert_eq!(profile, CpuProfile {
+            period_microseconds: 10000,
+            samples: vec![
+                CpuProfileSample { count: 3, addresses: vec![0x100, 0x201] },
+                CpuProfileSample { count: 1, addresses: vec![0x300] },
+            ],
+        });
+        assert_eq!(lookup_addresses(&profile).into_iter().collect::<Vec<u64>>(), vec![0x100, 0x200, 0x300]);
+
+        assert!(parse_cpu_profile(b"heap profile: 1: 2 [ 1: 2] @ growthz").is_err());
+        assert!(parse_cpu_profile(&cpu_profile_data(&[(3, &[0x100])])[..64]).is_err());
+    }
+
+    #[test]
+    fn unit_cpu_profile_top_and_folded() {
+        let profile = parse_cpu_profile(&cpu_profile_data(&[(3, &[0x100, 0x201]), (1, &[0x300, 0x201]), (2, &[0x201])])).unwrap();
+        let symbols = parse_symbols("0x0000000000000100\tyb::Leaf()\n0x0000000000000200\tyb::Caller()\n");
+        let allcpuprofilestacks = AllCpuProfileStacks { stacks: symbolise_cpu_profile("yb-1.local:9000", &profile, &symbols) };
+        assert_eq!(allcpuprofilestacks.stacks[0].frames, vec!["yb::Leaf()", "yb::Caller()"]);
+        assert_eq!(allcpuprofilestacks.stacks[0].seconds, 0.03);
+        assert_eq!(allcpuprofilestacks.stacks[1].frames, vec!["0x300", "yb::Caller()"]);
+        // the innermost frame is not a return address, so it is not adjusted.
+        assert_eq!(allcpuprofilestacks.stacks[2].frames, vec!["0x201"]);
+
+        let function_times = allcpuprofilestacks.function_times(&utility::set_regex(&None));
+        let caller = &function_times["yb-1.local:9000"]["yb::Caller()"];
+        assert_eq!(caller.flat_seconds, 0.0);
+        assert_eq!(format!("{:.2}", caller.cumulative_seconds), "0.04");
+
+        let folded_stacks = allcpuprofilestacks.folded_stacks(&utility::set_regex(&None));
+        assert_eq!(folded_stacks["yb::Caller();yb::Leaf()"], 3);
+        assert_eq!(folded_stacks["yb::Caller();0x300"], 1);
+    }
+
+    #[test]
+    fn unit_heap_profile_diff() {
+        let stack = |bytes: i64, frames: &[&str]| HeapProfileStack {
+            hostname_port: "yb-1.local:9000".to_string(),
+            count: 1,
+            bytes,
+            frames: frames.iter().map(|frame| frame.to_string()).collect(),
+        };
+        let mut heapprofilediff = HeapProfileDiff::new();
+        heapprofilediff.first_snapshot(AllHeapProfileStacks { stacks: vec![stack(100, &["a", "b"])] });
+        heapprofilediff.second_snapshot(AllHeapProfileStacks { stacks: vec![stack(300, &["a", "b"]), stack(50, &["a", "c"])] });
+        assert_eq!(heapprofilediff.btreeheapprofilediff[&("yb-1.local:9000".to_string(), vec!["a".to_string(), "b".to_string()])],
+                   HeapProfileDiffFields { first_count: 1, first_bytes: 100, second_count: 1, second_bytes: 300 });
+        assert_eq!(heapprofilediff.btreeheapprofilediff[&("yb-1.local:9000".to_string(), vec!["a".to_string(), "c".to_string()])].first_bytes, 0);
+    }
 
     #[tokio::test]
     async fn integration_parse_pprof_growth_tserver() {
@@ -93,6 +806,12 @@ mod tests {
         Pprof::read_and_write_pprof(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
     }
     #[tokio::test]
+    async fn integration_read_cpu_profile_tserver() {
+        let hostname = utility::get_hostname_tserver();
+        let port = utility::get_port_tserver();
+        Pprof::read_and_write_cpu_profile(&vec![&hostname], &vec![&port], -1, 1, 1).await.unwrap();
+    }
+    #[tokio::test]
     async fn integration_parse_pprof_growth_master() {
         // currently, the pprof "parsing" is not much parsing.
         // What currently is done, is that the hostname:port/pprof/growth output is stored in a file in the snapshot directory named <hostname>:<port>_pprof_growth.
@@ -3,11 +3,19 @@
 //! The `/pprof/growth` endpoint contains gperftools heap-profiling delta (growth) information.
 //! It is available on all master and tablet server endpoints, default port numbers 7000 (master), 9000, 12000 (tablet server).
 //!
-//! yb_stats doesn't do anything other than:
+//! For the snapshot, yb_stats doesn't do anything other than:
 //! - perform a HTTP GET and collect the result from the endpoint.
 //! - check if the returned data starts with "heap profile".
 //! - if so, save the result as "pprof_growth_*hostname:port*".
 //!
+//! For printing and the diff, the saved heap profiles are parsed into allocation stacks ([HeapProfileStack]).
+//!
+//! With `--cpu-profile <seconds>`, the `/pprof/profile?seconds=N` endpoint is read as well, which samples the CPU for the given time.
+//! The returned binary profile is saved as "pprof_cpu_*hostname:port*.prof", and the program counters in the profile
+//! are symbolised using the `/pprof/symbol` endpoint of the same server, and saved as "pprof_cpu_symbols_*hostname:port*.txt".
+//!
+mod structs;
 mod functions;
 
+pub use structs::*;
 pub use functions::*;
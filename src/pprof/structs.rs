@@ -0,0 +1,66 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+
+/// An allocation stack from a gperftools heap (growth) profile.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HeapProfileStack {
+    pub hostname_port: String,
+    /// The number of allocations with this stack.
+    pub count: i64,
+    /// The number of bytes allocated with this stack.
+    pub bytes: i64,
+    /// The frames of the stack, innermost first.
+    /// A frame is the function name if the profile contains symbols, otherwise the address.
+    pub frames: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct AllHeapProfileStacks {
+    pub stacks: Vec<HeapProfileStack>,
+}
+/// A CPU profile in the gperftools (legacy) binary CPU profile format, as returned by `/pprof/profile`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CpuProfile {
+    /// The sampling period in microseconds.
+    pub period_microseconds: u64,
+    pub samples: Vec<CpuProfileSample>,
+}
+/// A stack of program counters sampled one or more times.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CpuProfileSample {
+    pub count: u64,
+    /// The program counters, innermost first.
+    pub addresses: Vec<u64>,
+}
+/// A sampled stack from a CPU profile, with the frames symbolised where the server could resolve them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CpuProfileStack {
+    pub hostname_port: String,
+    pub samples: u64,
+    /// The CPU time of the samples: the number of samples times the sampling period.
+    pub seconds: f64,
+    /// The frames of the stack, innermost first.
+    pub frames: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct AllCpuProfileStacks {
+    pub stacks: Vec<CpuProfileStack>,
+}
+// diff
+/// BTreeMap for storing a heap profile diff struct per `hostname_port` and stack frames.
+type BTreeHeapProfileDiff = BTreeMap<(String, Vec<String>), HeapProfileDiffFields>;
+/// The wrapper struct for holding the btreemap holding the diff structs.
+#[derive(Debug, Default)]
+pub struct HeapProfileDiff {
+    pub btreeheapprofilediff: BTreeHeapProfileDiff,
+}
+/// The allocations of a stack in the first and second snapshot.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HeapProfileDiffFields {
+    pub first_count: i64,
+    pub first_bytes: i64,
+    pub second_count: i64,
+    pub second_bytes: i64,
+}
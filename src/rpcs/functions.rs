@@ -29,17 +29,55 @@ impl AllRpcs {
         ports: &Vec<&str>,
         snapshot_number: i32,
         parallel: usize,
+        redact_sql: &bool,
     ) -> Result<()>
     {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        let allrpcs = AllRpcs::read_rpcs(hosts, ports, parallel).await;
+        let mut allrpcs = AllRpcs::read_rpcs(hosts, ports, parallel).await;
+        if *redact_sql { allrpcs.redact_sql() };
         snapshot::save_snapshot_json(snapshot_number, "rpcs", allrpcs.rpcs)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
         Ok(())
     }
+    /// Replace the literals in the YSQL query and CQL sql_string texts with placeholders, see [utility::normalise_sql].
+    /// The CQL bind parameters are data only, and are removed.
+    pub fn redact_sql(&mut self)
+    {
+        for rpcs in self.rpcs.iter_mut()
+        {
+            match rpcs
+            {
+                Ysql { connections, .. } =>
+                    {
+                        for query in connections.iter_mut().filter_map(|connection| connection.query.as_mut())
+                        {
+                            *query = utility::normalise_sql(query);
+                        }
+                    },
+                Rpc { inbound_connections, .. } =>
+                    {
+                        for call_details in inbound_connections
+                            .iter_mut()
+                            .flatten()
+                            .filter_map(|inbound| inbound.calls_in_flight.as_mut())
+                            .flatten()
+                            .filter_map(|calls_in_flight| calls_in_flight.cql_details.as_mut())
+                            .flat_map(|cql_details| cql_details.call_details.iter_mut())
+                        {
+                            if let Some(sql_string) = call_details.sql_string.as_mut()
+                            {
+                                *sql_string = utility::normalise_sql(sql_string);
+                            }
+                            call_details.params = None;
+                        }
+                    },
+                _ => {},
+            }
+        }
+    }
     pub async fn read_rpcs(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
@@ -243,6 +281,7 @@ impl AllRpcs {
 
         Ok(())
     }
+    #[allow(clippy::collapsible_match)]
     fn print_details(
         &self,
         hostname: String,
@@ -611,11 +650,13 @@ pub async fn print_rpcs(
         {
             let mut allrpcs = AllRpcs::new();
             allrpcs.rpcs = snapshot::read_snapshot_json(snapshot_number, "rpcs")?;
+            if options.redact_sql { allrpcs.redact_sql() };
             allrpcs.print(&options.details_enable, &hostname_filter)?;
         }
         None =>
         {
-            let allrpcs = AllRpcs::read_rpcs(&hosts, &ports, parallel).await;
+            let mut allrpcs = AllRpcs::read_rpcs(&hosts, &ports, parallel).await;
+            if options.redact_sql { allrpcs.redact_sql() };
             allrpcs.print(&options.details_enable, &hostname_filter)?;
         }
     }
@@ -943,7 +984,7 @@ mod tests {
                                    .calls_in_flight.as_ref().unwrap()[0]
                                    .cql_details.as_ref().unwrap()
                                    .call_details[19]
-                                   .params.as_ref().unwrap(), "[2008-06, Louisiana, \0\0\0\0, n/a, \0\0\u{19}\u{00cd}, \0\0\u{14}L, n/a, \0\0\0\u{00da}, \0\0\0\0, \0\0\0\u{5}, \0\0\0\u{3}, n/a, \0\0\u{2}\u{0192}, \0\0\u{3}@, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, \0\04\u{00be}]");
+                                   .params.as_ref().unwrap(), "[2008-06, Louisiana, \0\0\0\0, n/a, \0\0\u{19}\u{00cd}, \0\0\u{14}L, n/a, \0\0\0\u{00da}, \0\0\0\0, \0\0\0\u{5}, \0\0\0\u{3}, n/a, \0\0\u{2}\u{0192}, \0\0\u{3}@, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, n/a, \0\0\x34\u{00be}]");
                 }
     }
 
@@ -1,8 +1,8 @@
 //! Snapshot functions
 //!
 use log::*;
-use std::{env, fs, io::{stdin, stdout, Write}, path::Path, sync::Arc, time::Instant};
-use chrono::Local;
+use std::{env, fs, io::{stdin, stdout, Write}, path::{Path, PathBuf}, sync::Arc, time::Instant};
+use chrono::{DateTime, Local};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
@@ -54,6 +54,17 @@ impl Snapshot {
             .with_context(|| format!("Cannot create directory: {}",&current_snapshot_directory.clone().into_os_string().into_string().unwrap()))?;
         Ok(snapshot_number)
     }
+    /// Read the timestamp of a snapshot number from the snapshots index file.
+    pub fn read_snapshot_timestamp(
+        snapshot_number: &str,
+    ) -> Result<DateTime<Local>>
+    {
+        Snapshot::read_snapshot_index()?
+            .iter()
+            .find(|row| row.number.to_string() == snapshot_number)
+            .map(|row| row.timestamp)
+            .with_context(|| format!("Unable to find snapshot number: {}", snapshot_number))
+    }
     /// This is a private function to read the snapshots index file, and return a vector with the snapshots.
     fn read_snapshot_index(
     ) -> Result<Vec<Snapshot>>
@@ -166,6 +177,23 @@ impl Snapshot {
         Ok((begin_snapshot.to_string(), end_snapshot.to_string(), begin_snapshot_row.clone()))
     }
 }
+/// Return the directory of a snapshot: yb_stats.snapshots/<snapshot_number> in the current working directory.
+pub fn snapshot_directory(
+    snapshot_number: &String,
+) -> Result<PathBuf>
+{
+    let current_directory = env::current_dir()
+        .with_context(|| "Cannot evaluate current working directory" )?;
+    Ok(current_directory.join("yb_stats.snapshots").join(snapshot_number))
+}
+/// Print the time between the first and the second snapshot in seconds.
+pub fn print_time_between_snapshots(
+    first_snapshot_time: &DateTime<Local>,
+    second_snapshot_time: &DateTime<Local>,
+)
+{
+    println!("Time between snapshots: {:8.3} seconds", (*second_snapshot_time - *first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+}
 /// This is the general yb_stat wide save_snapshot_json function.
 pub fn save_snapshot_json<T: Serialize>(
     snapshot_number: i32,
@@ -173,8 +201,7 @@ pub fn save_snapshot_json<T: Serialize>(
     vector: Vec<T>,
 ) -> Result<()>
 {
-    let current_directory = env::current_dir()?;
-    let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
+    let current_snapshot_directory = snapshot_directory(&snapshot_number.to_string())?;
 
     let filepath = &current_snapshot_directory.join(format!("{}.{}",filename, "json"));
     fs::write(filepath, serde_json::to_string(&vector)
@@ -188,8 +215,7 @@ pub fn read_snapshot_json<T: for<'de> Deserialize<'de>>(
     filename: &str,
 ) -> Result<Vec<T>>
 {
-    let current_directory = env::current_dir()?;
-    let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number);
+    let current_snapshot_directory = snapshot_directory(snapshot_number)?;
     let filepath = &current_snapshot_directory.join(format!("{}.{}", filename, "json"));
 
     let vector = {
@@ -203,6 +229,7 @@ pub fn read_snapshot_json<T: for<'de> Deserialize<'de>>(
 pub async fn perform_snapshot(
     hosts: Vec<&'static str>,
     ports: Vec<&'static str>,
+    prometheus_targets: Vec<node_exporter::PrometheusTarget>,
     parallel: usize,
     options: &Opts,
 ) -> Result<()> {
@@ -215,20 +242,24 @@ pub async fn perform_snapshot(
     let arc_hosts = Arc::new(hosts);
     let arc_ports = Arc::new(ports);
     let arc_extra_data = Arc::new(options.extra_data);
+    let arc_redact_sql = Arc::new(options.redact_sql);
+    let arc_metrics_source = Arc::new(options.metrics_source.clone());
 
     let mut handles = vec![];
 
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
+    let arc_metrics_source_clone = arc_metrics_source.clone();
     let handle = tokio::spawn(async move {
-        metrics::AllMetricEntity::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        metrics::AllMetricEntity::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel, &arc_metrics_source_clone).await.unwrap();
     });
     handles.push(handle);
 
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
+    let arc_redact_sql_clone = arc_redact_sql.clone();
     let handle = tokio::spawn(async move {
-        statements::AllStatements::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        statements::AllStatements::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel, &arc_redact_sql_clone).await.unwrap();
     });
     handles.push(handle);
 
@@ -239,6 +270,13 @@ pub async fn perform_snapshot(
     });
     handles.push(handle);
 
+    if !prometheus_targets.is_empty() {
+        let handle = tokio::spawn(async move {
+            node_exporter::AllNodeExporter::perform_prometheus_targets_snapshot(&prometheus_targets, snapshot_number, parallel).await.unwrap();
+        });
+        handles.push(handle);
+    }
+
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
     let handle = tokio::spawn(async move {
@@ -313,8 +351,9 @@ pub async fn perform_snapshot(
 
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
+    let arc_redact_sql_clone = arc_redact_sql.clone();
     let handle = tokio::spawn(async move {
-        rpcs::AllRpcs::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        rpcs::AllRpcs::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel, &arc_redact_sql_clone).await.unwrap();
     });
     handles.push(handle);
 
@@ -325,6 +364,15 @@ pub async fn perform_snapshot(
     });
     handles.push(handle);
 
+    if let Some(seconds) = options.cpu_profile {
+        let arc_hosts_clone = arc_hosts.clone();
+        let arc_ports_clone = arc_ports.clone();
+        let handle = tokio::spawn(async move {
+            pprof::Pprof::perform_cpu_profile_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel, seconds).await.unwrap();
+        });
+        handles.push(handle);
+    };
+
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
     let handle = tokio::spawn(async move {
@@ -413,12 +461,15 @@ pub async fn perform_snapshot(
 /// - metrics (value, coarse_histogram/countsum, ysql/countsumrows)
 /// - statements (ysql)
 /// - node_exporter
+/// - prometheus targets
 /// - entities (read via master leader)
 /// - masters (read via master leader)
 /// - tablet servers (read via master leader)
 /// - vars
 /// - versions
+/// - gflags
 /// - health check (read via master leader)
+/// - loglines between the snapshots (with `--log-diff`)
 pub async fn snapshot_diff(
     options: &Opts,
 ) -> Result<()>
@@ -444,6 +495,8 @@ pub async fn snapshot_diff(
     let nodeexporter_diff = node_exporter::NodeExporterDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
     nodeexporter_diff.print(&hostname_filter, &stat_name_filter, &options.gauges_enable, &options.details_enable);
 
+    node_exporter::print_prometheus_targets_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp, options)?;
+
     let entities_diff = entities::EntitiesDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
     entities_diff.print();
 
@@ -459,9 +512,16 @@ pub async fn snapshot_diff(
     let versions_diff = versions::VersionsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
     versions_diff.print(&hostname_filter);
 
+    let gflags_diff = gflags::GFlagsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    gflags_diff.print(&hostname_filter, &utility::set_regex(&None));
+
     let healthcheck_diff = health_check::HealthCheckDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
     healthcheck_diff.print();
 
+    if options.log_diff {
+        loglines::print_loglines_between_snapshots(&begin_snapshot, &end_snapshot, options)?;
+    }
+
     Ok(())
 }
 
@@ -472,6 +532,7 @@ pub async fn snapshot_diff(
 /// - tablet servers (read via master leader)
 /// - vars
 /// - versions
+/// - gflags
 /// - health check (read via master leader)
 ///
 /// The purpose of this function is to quickly determine significant changes for a cluster, not to
@@ -505,6 +566,9 @@ pub async fn snapshot_nonmetrics_diff(
     let versions_diff = versions::VersionsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
     versions_diff.print(&hostname_filter);
 
+    let gflags_diff = gflags::GFlagsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    gflags_diff.print(&hostname_filter, &utility::set_regex(&None));
+
     let healthcheck_diff = health_check::HealthCheckDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
     healthcheck_diff.print();
 
@@ -543,9 +607,10 @@ pub async fn adhoc_metrics_diff(
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
     let details_enable = options.details_enable;
+    let metrics_source = options.metrics_source.clone();
 
     let handle = tokio::spawn(async move {
-        clone_metrics.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel, details_enable).await;
+        clone_metrics.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel, details_enable, &metrics_source).await;
     });
     handles.push(handle);
 
@@ -569,9 +634,10 @@ pub async fn adhoc_metrics_diff(
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
     let details_enable = options.details_enable;
+    let metrics_source = options.metrics_source.clone();
 
     let handle = tokio::spawn(async move {
-        clone_metrics.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &first_snapshot_time, details_enable).await;
+        clone_metrics.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &first_snapshot_time, details_enable, &metrics_source).await;
     });
     handles.push(handle);
 
@@ -581,12 +647,77 @@ pub async fn adhoc_metrics_diff(
 
     info!("ad-hoc metrics diff second snapshot end: {:?}", timer.elapsed());
 
-    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+    print_time_between_snapshots(&first_snapshot_time, &second_snapshot_time);
     metrics.lock().await.print(&hostname_filter, &stat_name_filter, &table_name_filter, &options.details_enable, &options.gauges_enable).await;
 
     Ok(())
 }
 
+/// Take "adhoc" (in memory) snapshots of the threads only, and show the CPU used between the snapshots.
+pub async fn adhoc_threads_diff(
+    hosts: Vec<&'static str>,
+    ports: Vec<&'static str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    info!("ad-hoc threads diff first snapshot begin");
+    let timer = Instant::now();
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let first_snapshot_time = Local::now();
+
+    let threads = Arc::new(Mutex::new(threads::ThreadsDiff::new()));
+
+    let hosts = Arc::new(hosts);
+    let ports = Arc::new(ports);
+
+    let mut handles = vec![];
+
+    let clone_threads = threads.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_threads.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    info!("ad-hoc threads diff first snapshot end: {:?}", timer.elapsed());
+
+    println!("Begin ad-hoc in-memory snapshot created, press enter to create end snapshot for difference calculation.");
+    let mut input = String::new();
+    stdin().read_line(&mut input).expect("failed");
+
+    info!("ad-hoc threads diff second snapshot begin");
+    let timer = Instant::now();
+
+    let second_snapshot_time = Local::now();
+
+    let mut handles = vec![];
+
+    let clone_threads = threads.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_threads.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &first_snapshot_time).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    info!("ad-hoc threads diff second snapshot end: {:?}", timer.elapsed());
+
+    print_time_between_snapshots(&first_snapshot_time, &second_snapshot_time);
+    threads.lock().await.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
 /// Take "adhoc" (in memory) snapshots of node_exporter only:
 ///
 /// The idea here to reduce output when it's know lots of 'entities'/objects are created, or other
@@ -651,8 +782,19 @@ pub async fn adhoc_node_exporter_diff(
 
     info!("ad-hoc node_exporter diff second snapshot end: {:?}", timer.elapsed());
 
-    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
-    node_exporter.lock().await.print(&hostname_filter, &stat_name_filter, &options.gauges_enable, &options.details_enable);
+    print_time_between_snapshots(&first_snapshot_time, &second_snapshot_time);
+    if options.adhoc_iostat_diff
+    {
+        node_exporter.lock().await.print_iostat(&hostname_filter, &options.details_enable);
+    }
+    else if options.adhoc_node_summary_diff
+    {
+        node_exporter.lock().await.print_summary(&hostname_filter, &options.details_enable);
+    }
+    else
+    {
+        node_exporter.lock().await.print(&hostname_filter, &stat_name_filter, &options.gauges_enable, &options.details_enable);
+    }
 
     Ok(())
 }
@@ -678,6 +820,7 @@ pub async fn adhoc_nonmetrics_diff(
     let versions = Arc::new(Mutex::new(versions::VersionsDiff::new()));
     let vars = Arc::new(Mutex::new(vars::VarsDiff::new()));
     let health_check = Arc::new(Mutex::new(health_check::HealthCheckDiff::new()));
+    let gflags = Arc::new(Mutex::new(gflags::GFlagsDiff::new()));
 
     let hosts = Arc::new(hosts);
     let ports = Arc::new(ports);
@@ -733,6 +876,14 @@ pub async fn adhoc_nonmetrics_diff(
     });
     handles.push(handle);
 
+    let clone_gflags = gflags.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_gflags.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
 
     for handle in handles {
         handle.await.unwrap();
@@ -796,17 +947,26 @@ pub async fn adhoc_nonmetrics_diff(
     });
     handles.push(handle);
 
+    let clone_gflags = gflags.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_gflags.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
     for handle in handles {
         handle.await.unwrap();
     }
     info!("ad-hoc metrics diff second snapshot end: {:?}", timer.elapsed());
 
-    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+    print_time_between_snapshots(&first_snapshot_time, &second_snapshot_time);
     entities.lock().await.print();
     masters.lock().await.print();
     tablet_servers.lock().await.print();
     vars.lock().await.print();
     versions.lock().await.print(&hostname_filter);
+    gflags.lock().await.print(&hostname_filter, &utility::set_regex(&None));
     health_check.lock().await.print();
 
     Ok(())
@@ -820,6 +980,7 @@ pub async fn adhoc_nonmetrics_diff(
 /// - tablet servers (read via master leader)
 /// - vars
 /// - versions
+/// - gflags
 pub async fn adhoc_diff(
     hosts: Vec<&'static str>,
     ports: Vec<&'static str>,
@@ -844,11 +1005,13 @@ pub async fn adhoc_diff(
     let tablet_servers = Arc::new(Mutex::new(tablet_servers::TabletServersDiff::new()));
     let versions = Arc::new(Mutex::new(versions::VersionsDiff::new()));
     let vars = Arc::new(Mutex::new(vars::VarsDiff::new()));
+    let gflags = Arc::new(Mutex::new(gflags::GFlagsDiff::new()));
     let health_check = Arc::new(Mutex::new(health_check::HealthCheckDiff::new()));
 
     let hosts = Arc::new(hosts);
     let ports = Arc::new(ports);
     let details_enable = options.details_enable;
+    let metrics_source = options.metrics_source.clone();
 
     let mut handles = vec![];
 
@@ -856,7 +1019,7 @@ pub async fn adhoc_diff(
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
     let handle = tokio::spawn(async move {
-        clone_metrics.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel, details_enable).await;
+        clone_metrics.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel, details_enable, &metrics_source).await;
     });
     handles.push(handle);
 
@@ -916,6 +1079,14 @@ pub async fn adhoc_diff(
     });
     handles.push(handle);
 
+    let clone_gflags = gflags.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_gflags.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
     let clone_health_check = health_check.clone();
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
@@ -943,9 +1114,10 @@ pub async fn adhoc_diff(
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
     let details_enable = options.details_enable;
+    let metrics_source = options.metrics_source.clone();
 
     let handle = tokio::spawn(async move {
-        clone_metrics.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &first_snapshot_time, details_enable).await;
+        clone_metrics.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &first_snapshot_time, details_enable, &metrics_source).await;
     });
     handles.push(handle);
 
@@ -1005,6 +1177,14 @@ pub async fn adhoc_diff(
     });
     handles.push(handle);
 
+    let clone_gflags = gflags.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_gflags.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
     let clone_health_check = health_check.clone();
     let clone_hosts = hosts.clone();
     let clone_ports = ports.clone();
@@ -1018,7 +1198,7 @@ pub async fn adhoc_diff(
     }
     info!("ad-hoc metrics diff second snapshot end: {:?}", timer.elapsed());
 
-    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+    print_time_between_snapshots(&first_snapshot_time, &second_snapshot_time);
     metrics.lock().await.print(&hostname_filter, &stat_name_filter, &table_name_filter, &options.details_enable, &options.gauges_enable).await;
     statements.lock().await.print(&hostname_filter, options.sql_length).await;
     node_exporter.lock().await.print(&hostname_filter, &stat_name_filter, &options.gauges_enable, &options.details_enable);
@@ -1027,6 +1207,7 @@ pub async fn adhoc_diff(
     tablet_servers.lock().await.print();
     vars.lock().await.print();
     versions.lock().await.print(&hostname_filter);
+    gflags.lock().await.print(&hostname_filter, &utility::set_regex(&None));
     health_check.lock().await.print();
 
     Ok(())
@@ -1,14 +1,15 @@
 //! The the impls and functions.
 //!
 use chrono::{DateTime, Local};
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::BTreeMap, sync::mpsc::channel, time::Instant};
 use regex::Regex;
 use substring::Substring;
 use log::*;
+use colored::*;
 use anyhow::Result;
 use crate::utility;
 use crate::snapshot;
-use crate::statements::{Statements, AllStatements, StatementsDiff, GroupedStatements};
+use crate::statements::{Statement, Statements, AllStatements, StatementsDiff, GroupedStatements, ClusterStatement, ClusterStatementsSort, StatementsSort};
 use crate::Opts;
 
 impl AllStatements {
@@ -17,13 +18,15 @@ impl AllStatements {
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         snapshot_number: i32,
-        parallel: usize
+        parallel: usize,
+        redact_sql: &bool,
     ) -> Result<()>
     {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        let allstatements = AllStatements::read_statements(hosts, ports, parallel).await;
+        let mut allstatements = AllStatements::read_statements(hosts, ports, parallel).await;
+        if *redact_sql { allstatements.redact_sql() };
         snapshot::save_snapshot_json(snapshot_number, "statements", allstatements.statements)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
@@ -77,6 +80,14 @@ impl AllStatements {
         let data_from_http = utility::http_get(host, port, "statements");
         AllStatements::parse_statements(data_from_http, host, port)
     }
+    /// Replace the literals in the query text with placeholders, see [utility::normalise_sql].
+    pub fn redact_sql(&mut self)
+    {
+        self.statements
+            .iter_mut()
+            .flat_map(|statements| statements.statements.iter_mut())
+            .for_each(|statement| statement.query = utility::normalise_sql(&statement.query));
+    }
     fn parse_statements(
         http_data: String,
         host: &str,
@@ -88,7 +99,68 @@ impl AllStatements {
                 Statements::default()
             })
     }
-    // there is no print function
+    /// Collect the statements of the servers matching the hostname filter, sorted descending by:
+    /// - calls
+    /// - total_time (default)
+    /// - avg_time
+    /// - min_time
+    /// - max_time
+    /// - stddev_time
+    /// - rows
+    fn sorted_statements(
+        &self,
+        hostname_filter: &Regex,
+        statements_sort: StatementsSort,
+    ) -> Vec<(&String, &Statement)>
+    {
+        let mut statements: Vec<(&String, &Statement)> = Vec::new();
+        for server in &self.statements
+        {
+            let hostname_port = server.hostname_port.as_ref().expect("hostname:port should be set");
+            if hostname_filter.is_match(hostname_port)
+            {
+                statements.extend(server.statements.iter().map(|statement| (hostname_port, statement)));
+            }
+        }
+        match statements_sort
+        {
+            StatementsSort::Calls => statements.sort_by_key(|(_, statement)| std::cmp::Reverse(statement.calls)),
+            StatementsSort::TotalTime => statements.sort_by(|(_, a), (_, b)| b.total_time.total_cmp(&a.total_time)),
+            StatementsSort::AvgTime => statements.sort_by(|(_, a), (_, b)| b.mean_time.total_cmp(&a.mean_time)),
+            StatementsSort::MinTime => statements.sort_by(|(_, a), (_, b)| b.min_time.total_cmp(&a.min_time)),
+            StatementsSort::MaxTime => statements.sort_by(|(_, a), (_, b)| b.max_time.total_cmp(&a.max_time)),
+            StatementsSort::StddevTime => statements.sort_by(|(_, a), (_, b)| b.stddev_time.total_cmp(&a.stddev_time)),
+            StatementsSort::Rows => statements.sort_by_key(|(_, statement)| std::cmp::Reverse(statement.rows)),
+        }
+        statements
+    }
+    /// Print the statements per server, in the way pg_stat_statements shows them.
+    pub async fn print(
+        &self,
+        hostname_filter: &Regex,
+        sql_length: usize,
+        statements_sort: StatementsSort,
+    )
+    {
+        let statements = self.sorted_statements(hostname_filter, statements_sort);
+
+        println!("{:20} {:>10} {:>15} {:>12} {:>12} {:>12} {:>12} {:>10} query", "hostname_port", "calls", "tot ms", "min ms", "max ms", "mean ms", "stddev ms", "rows");
+        for (hostname_port, statement) in statements
+        {
+            let adaptive_length = if statement.query.len() < sql_length { statement.query.len() } else { sql_length };
+            println!("{:20} {:>10} {:>15.3} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>10} {}",
+                     hostname_port,
+                     statement.calls,
+                     statement.total_time,
+                     statement.min_time,
+                     statement.max_time,
+                     statement.mean_time,
+                     statement.stddev_time,
+                     statement.rows,
+                     statement.query.substring(0, adaptive_length).escape_default(),
+            );
+        }
+    }
 }
 
 impl StatementsDiff {
@@ -121,13 +193,14 @@ impl StatementsDiff {
             for statement in statements.statements
             {
                 self.btreestatementsdiff
-                    .entry((statements.hostname_port.clone().expect("hostname:port should be set"), statement.query))
+                    .entry((statements.hostname_port.clone().expect("hostname:port should be set"), utility::normalise_sql(&statement.query)))
                     .and_modify(|statementdiff| {
                         statementdiff.first_calls += statement.calls;
                         statementdiff.first_total_time += statement.total_time;
                         statementdiff.first_rows += statement.rows;
                     })
                     .or_insert( GroupedStatements {
+                        query_id: statement.query_id,
                         first_snapshot_time: statements.timestamp.expect("timestamp should be set"),
                         first_calls: statement.calls,
                         first_total_time: statement.total_time,
@@ -149,13 +222,15 @@ impl StatementsDiff {
             for statement in statements.statements
             {
                 self.btreestatementsdiff
-                    .entry((statements.hostname_port.clone().expect("hostname:port should be set"), statement.query))
+                    .entry((statements.hostname_port.clone().expect("hostname:port should be set"), utility::normalise_sql(&statement.query)))
                     .and_modify(|statementdiff| {
+                        statementdiff.second_snapshot_time = statements.timestamp.expect("timestamp should be set");
                         statementdiff.second_calls += statement.calls;
                         statementdiff.second_total_time += statement.total_time;
                         statementdiff.second_rows += statement.rows;
                     })
                     .or_insert( GroupedStatements {
+                        query_id: statement.query_id,
                         first_snapshot_time: *begin_snapshot_time,
                         second_snapshot_time: statements.timestamp.expect("timestamp should be set"),
                         second_calls: statement.calls,
@@ -174,25 +249,114 @@ impl StatementsDiff {
     {
         for ((hostname, query), statements_row) in &self.btreestatementsdiff
         {
+            // the calls of a statement can only go down if its statistics have been reset,
+            // which can also happen for a single statement when pg_stat_statements evicts it,
+            // so the reset is detected per statement instead of for the whole server.
+            let (calls, reset) = utility::counter_difference(statements_row.first_calls, statements_row.second_calls, false);
+            let (total_time, _) = utility::counter_difference(statements_row.first_total_time, statements_row.second_total_time, false);
+            let (rows, _) = utility::counter_difference(statements_row.first_rows, statements_row.second_rows, false);
             if hostname_filter.is_match(hostname)
-                && statements_row.second_calls - statements_row.first_calls != 0
+                // a statement that is not in the second snapshot has been removed.
+                && statements_row.second_calls != 0
+                && calls != 0
             {
                 let adaptive_length = if query.len() < sql_length { query.len() } else { sql_length };
                 trace!("PRINT {}: second_calls: {}, first_calls: {}, query: {}", hostname, statements_row.second_calls, statements_row.first_calls, query.substring(0,adaptive_length).escape_default());
-                println!("{:20} {:10} avg: {:15.3} tot: {:15.3} ms avg: {:10} tot: {:10} rows: {:0adaptive_length$}",
+                println!("{:20} {:10} avg: {:15.3} tot: {:15.3} ms avg: {:10} tot: {:10} rows: {:0adaptive_length$} {}",
                          hostname,
-                         statements_row.second_calls - statements_row.first_calls,
-                         (statements_row.second_total_time - statements_row.first_total_time) / (statements_row.second_calls as f64 - statements_row.first_calls as f64),
-                         statements_row.second_total_time - statements_row.first_total_time,
-                         (statements_row.second_rows - statements_row.first_rows) / (statements_row.second_calls - statements_row.first_calls),
-                         statements_row.second_rows - statements_row.first_rows,
-                         query.substring(0, adaptive_length).escape_default()
+                         calls,
+                         total_time / calls as f64,
+                         total_time,
+                         rows / calls,
+                         rows,
+                         query.substring(0, adaptive_length).escape_default(),
+                         if reset { "reset".red() } else { "".normal() },
                 );
             } else {
                 trace!("SKIP {}: second_calls: {}, first_calls: {}, query: {}", hostname, statements_row.second_calls, statements_row.first_calls, query.escape_default());
             }
         }
     }
+    /// Group the statements of all YSQL servers by query_id, or by the normalised query text if query_id is not available.
+    fn group_cluster(
+        &self,
+        hostname_filter: &Regex,
+    ) -> Vec<ClusterStatement>
+    {
+        let mut clusterstatements: BTreeMap<String, ClusterStatement> = BTreeMap::new();
+        for ((hostname, query), statements_row) in self.btreestatementsdiff
+            .iter()
+            .filter(|((hostname, _), _)| hostname_filter.is_match(hostname))
+        {
+            let (calls, _) = utility::counter_difference(statements_row.first_calls, statements_row.second_calls, false);
+            let (total_time, _) = utility::counter_difference(statements_row.first_total_time, statements_row.second_total_time, false);
+            let (rows, _) = utility::counter_difference(statements_row.first_rows, statements_row.second_rows, false);
+            // a statement that is not in the second snapshot has been removed.
+            if statements_row.second_calls == 0
+                || calls == 0
+            {
+                continue;
+            }
+            let key = match statements_row.query_id
+            {
+                Some(query_id) => query_id.to_string(),
+                None => query.to_string(),
+            };
+            let seconds = (statements_row.second_snapshot_time - statements_row.first_snapshot_time).num_milliseconds() as f64 / 1000_f64;
+            let clusterstatement = clusterstatements.entry(key).or_insert(ClusterStatement {
+                query: query.to_string(),
+                ..Default::default()
+            });
+            clusterstatement.servers.insert(hostname.to_string());
+            clusterstatement.calls += calls;
+            clusterstatement.total_time += total_time;
+            clusterstatement.rows += rows;
+            clusterstatement.seconds = clusterstatement.seconds.max(seconds);
+        }
+        clusterstatements.into_values().collect()
+    }
+    /// Print the statements of all YSQL servers as a single, cluster-wide, report.
+    ///
+    /// The statements are grouped by query_id, and sorted by:
+    /// - calls
+    /// - total_time (default)
+    /// - avg_time
+    /// - rows
+    ///
+    /// The share column shows the percentage of the total time of all statements in the report.
+    pub async fn print_cluster(
+        &self,
+        hostname_filter: &Regex,
+        sql_length: usize,
+        statements_sort: ClusterStatementsSort,
+    )
+    {
+        let mut clusterstatements = self.group_cluster(hostname_filter);
+        match statements_sort
+        {
+            ClusterStatementsSort::Calls => clusterstatements.sort_by_key(|r| std::cmp::Reverse(r.calls)),
+            ClusterStatementsSort::TotalTime => clusterstatements.sort_by(|a, b| b.total_time.total_cmp(&a.total_time)),
+            ClusterStatementsSort::AvgTime => clusterstatements.sort_by(|a, b| (b.total_time / b.calls as f64).total_cmp(&(a.total_time / a.calls as f64))),
+            ClusterStatementsSort::Rows => clusterstatements.sort_by_key(|r| std::cmp::Reverse(r.rows)),
+        }
+        let cluster_total_time: f64 = clusterstatements.iter().map(|r| r.total_time).sum();
+
+        println!("{:>7} {:>10} {:>12} {:>15} {:>15} {:>7} {:>10} query", "servers", "calls", "calls/s", "avg ms", "tot ms", "share", "rows/call");
+        for clusterstatement in clusterstatements
+        {
+            let adaptive_length = if clusterstatement.query.len() < sql_length { clusterstatement.query.len() } else { sql_length };
+            println!("{:>7} {:>10} {:>12.3} {:>15.3} {:>15.3} {:>6.2}% {:>10.1} {}",
+                     clusterstatement.servers.len(),
+                     clusterstatement.calls,
+                     if clusterstatement.seconds > 0.0 { clusterstatement.calls as f64 / clusterstatement.seconds } else { 0.0 },
+                     clusterstatement.total_time / clusterstatement.calls as f64,
+                     clusterstatement.total_time,
+                     if cluster_total_time > 0.0 { clusterstatement.total_time / cluster_total_time * 100_f64 } else { 0.0 },
+                     clusterstatement.rows as f64 / clusterstatement.calls as f64,
+                     clusterstatement.query.substring(0, adaptive_length).escape_default(),
+            );
+        }
+    }
     pub async fn adhoc_read_first_snapshot(
         &mut self,
         hosts: &Vec<&str>,
@@ -236,6 +400,51 @@ pub async fn statements_diff(
     Ok(())
 }
 
+pub async fn print_statements(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    match options.print_statements.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allstatements = AllStatements::new();
+            allstatements.statements = snapshot::read_snapshot_json(snapshot_number, "statements")?;
+            if options.redact_sql { allstatements.redact_sql() };
+            allstatements.print(&hostname_filter, options.sql_length, options.print_statements_sort).await;
+        }
+        None => {
+            let mut allstatements = AllStatements::read_statements(&hosts, &ports, parallel).await;
+            if options.redact_sql { allstatements.redact_sql() };
+            allstatements.print(&hostname_filter, options.sql_length, options.print_statements_sort).await;
+        }
+    }
+    Ok(())
+}
+
+pub async fn statements_cluster_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let sql_length: usize = options.sql_length;
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let statementsdiff = StatementsDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
+    statementsdiff.print_cluster(&hostname_filter, sql_length, options.statements_sort).await;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +496,77 @@ mod tests {
         assert_eq!(result.statements.len(), 3);
     }
     #[test]
+    fn unit_statements_sorted_statements() {
+        // every statement is the highest for a different sort option.
+        let statements_json = r#"
+{
+    "statements":
+    [
+        {
+            "query_id": 1,
+            "query": "select most calls",
+            "calls": 100,
+            "total_time": 20.0,
+            "min_time": 0.1,
+            "max_time": 0.5,
+            "mean_time": 0.2,
+            "stddev_time": 0.1,
+            "rows": 100
+        },
+        {
+            "query_id": 2,
+            "query": "select most total time",
+            "calls": 10,
+            "total_time": 50.0,
+            "min_time": 4.0,
+            "max_time": 6.0,
+            "mean_time": 5.0,
+            "stddev_time": 0.5,
+            "rows": 1000
+        },
+        {
+            "query_id": 3,
+            "query": "select slowest",
+            "calls": 1,
+            "total_time": 30.0,
+            "min_time": 30.0,
+            "max_time": 30.0,
+            "mean_time": 30.0,
+            "stddev_time": 0.0,
+            "rows": 1
+        },
+        {
+            "query_id": 4,
+            "query": "select most variable",
+            "calls": 2,
+            "total_time": 40.0,
+            "min_time": 1.0,
+            "max_time": 39.0,
+            "mean_time": 20.0,
+            "stddev_time": 19.0,
+            "rows": 2
+        }
+    ]
+}"#.to_string();
+        let mut statements = AllStatements::parse_statements(statements_json, "", "");
+        statements.hostname_port = Some("yb-1.local:13000".to_string());
+        let allstatements = AllStatements { statements: vec![statements] };
+        let hostname_filter = utility::set_regex(&None);
+        let query_ids = |statements_sort| allstatements.sorted_statements(&hostname_filter, statements_sort)
+            .iter()
+            .map(|(_, statement)| statement.query_id.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(query_ids(StatementsSort::Calls), vec![1, 2, 4, 3]);
+        assert_eq!(query_ids(StatementsSort::TotalTime), vec![2, 4, 3, 1]);
+        assert_eq!(query_ids(StatementsSort::AvgTime), vec![3, 4, 2, 1]);
+        assert_eq!(query_ids(StatementsSort::MinTime), vec![3, 2, 4, 1]);
+        assert_eq!(query_ids(StatementsSort::MaxTime), vec![4, 3, 2, 1]);
+        assert_eq!(query_ids(StatementsSort::StddevTime), vec![4, 2, 1, 3]);
+        assert_eq!(query_ids(StatementsSort::Rows), vec![2, 1, 4, 3]);
+        // a hostname filter that doesn't match any server should not return statements.
+        assert!(allstatements.sorted_statements(&utility::set_regex(&Some("yb-2".to_string())), StatementsSort::TotalTime).is_empty());
+    }
+    #[test]
     fn unit_parse_statements_multiple_statements() {
         // This is a very simple example of the statements json.
         let statements_json = r#"
@@ -338,6 +618,150 @@ mod tests {
         assert_eq!(result.statements[0].min_time, 13.76067);
     }
 
+    #[test]
+    fn unit_statements_group_cluster() {
+        // the same query on two servers, with the same query_id, but with different whitespace.
+        let first_json = r#"
+{
+    "statements":
+    [
+        {
+            "query_id": 1950307723985771540,
+            "query": "select 1",
+            "calls": 1,
+            "total_time": 1.0,
+            "min_time": 1.0,
+            "max_time": 1.0,
+            "mean_time": 1.0,
+            "stddev_time": 0.0,
+            "rows": 1
+        }
+    ]
+}"#.to_string();
+        let second_json = first_json.replace("\"calls\": 1,", "\"calls\": 11,").replace("\"total_time\": 1.0", "\"total_time\": 11.0").replace("\"rows\": 1", "\"rows\": 11");
+        let mut statementsdiff = StatementsDiff::new();
+        let mut allstatements = AllStatements::new();
+        for hostname_port in ["yb-1.local:13000", "yb-2.local:13000"]
+        {
+            let mut statements = AllStatements::parse_statements(first_json.replace("select 1", if hostname_port.starts_with("yb-1") { "select 1" } else { "select  1" }), "", "");
+            statements.hostname_port = Some(hostname_port.to_string());
+            statements.timestamp = Some(Local::now());
+            allstatements.statements.push(statements);
+        }
+        statementsdiff.first_snapshot(allstatements);
+        let mut allstatements = AllStatements::new();
+        for hostname_port in ["yb-1.local:13000", "yb-2.local:13000"]
+        {
+            let mut statements = AllStatements::parse_statements(second_json.replace("select 1", if hostname_port.starts_with("yb-1") { "select 1" } else { "select  1" }), "", "");
+            statements.hostname_port = Some(hostname_port.to_string());
+            statements.timestamp = Some(Local::now());
+            allstatements.statements.push(statements);
+        }
+        statementsdiff.second_snapshot(allstatements, &Local::now());
+        let result = statementsdiff.group_cluster(&Regex::new(".*").unwrap());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].servers.len(), 2);
+        assert_eq!(result[0].calls, 20);
+        assert_eq!(result[0].total_time, 20.0);
+        assert_eq!(result[0].query, "select ?");
+    }
+
+    #[test]
+    fn unit_statements_diff_statement_reset() {
+        // two statements on the same server, of which only the second has been reset between the snapshots,
+        // for example because pg_stat_statements evicted it.
+        let first_json = r#"
+{
+    "statements":
+    [
+        {
+            "query_id": 1,
+            "query": "select a from t",
+            "calls": 10,
+            "total_time": 10.0,
+            "min_time": 1.0,
+            "max_time": 1.0,
+            "mean_time": 1.0,
+            "stddev_time": 0.0,
+            "rows": 10
+        },
+        {
+            "query_id": 2,
+            "query": "select b from t",
+            "calls": 10,
+            "total_time": 10.0,
+            "min_time": 1.0,
+            "max_time": 1.0,
+            "mean_time": 1.0,
+            "stddev_time": 0.0,
+            "rows": 10
+        }
+    ]
+}"#.to_string();
+        let second_json = r#"
+{
+    "statements":
+    [
+        {
+            "query_id": 1,
+            "query": "select a from t",
+            "calls": 15,
+            "total_time": 15.0,
+            "min_time": 1.0,
+            "max_time": 1.0,
+            "mean_time": 1.0,
+            "stddev_time": 0.0,
+            "rows": 15
+        },
+        {
+            "query_id": 2,
+            "query": "select b from t",
+            "calls": 3,
+            "total_time": 3.0,
+            "min_time": 1.0,
+            "max_time": 1.0,
+            "mean_time": 1.0,
+            "stddev_time": 0.0,
+            "rows": 3
+        }
+    ]
+}"#.to_string();
+        let mut statementsdiff = StatementsDiff::new();
+        let mut statements = AllStatements::parse_statements(first_json, "", "");
+        statements.hostname_port = Some("yb-1.local:13000".to_string());
+        statements.timestamp = Some(Local::now());
+        statementsdiff.first_snapshot(AllStatements { statements: vec![statements] });
+        let mut statements = AllStatements::parse_statements(second_json, "", "");
+        statements.hostname_port = Some("yb-1.local:13000".to_string());
+        statements.timestamp = Some(Local::now());
+        statementsdiff.second_snapshot(AllStatements { statements: vec![statements] }, &Local::now());
+        let result = statementsdiff.group_cluster(&Regex::new(".*").unwrap());
+        assert_eq!(result.len(), 2);
+        // the statement that was not reset shows the difference.
+        assert_eq!(result[0].query, "select a from t");
+        assert_eq!(result[0].calls, 5);
+        assert_eq!(result[0].total_time, 5.0);
+        assert_eq!(result[0].rows, 5);
+        // the statement that was reset shows the second snapshot values.
+        assert_eq!(result[1].query, "select b from t");
+        assert_eq!(result[1].calls, 3);
+        assert_eq!(result[1].total_time, 3.0);
+        assert_eq!(result[1].rows, 3);
+    }
+
+    #[test]
+    fn unit_normalise_sql() {
+        assert_eq!(utility::normalise_sql("select * from t where id = 42 and name = 'O''Brien'"), "select * from t where id = ? and name = '?'");
+        assert_eq!(utility::normalise_sql(r"select * from t where a = E'it\'s secret' and b = e'back\\' and c = 1"), "select * from t where a = '?' and b = '?' and c = ?");
+        assert_eq!(utility::normalise_sql(r"select e, code'x', E'\\\'' from t"), "select e, code'?', '?' from t");
+        assert_eq!(utility::normalise_sql("select * from t where name = 'O''Brien' or password = 'secret"), "select * from t where name = '?' or password = '?'");
+        assert_eq!(utility::normalise_sql(r"select * from t where name = E'unclosed\' secret"), "select * from t where name = '?'");
+        assert_eq!(utility::normalise_sql("select t1.c2 from \"T 9\" t1 where x > 1.5e-3 limit $1"), "select t1.c2 from \"T 9\" t1 where x > ? limit $1");
+        assert_eq!(utility::normalise_sql("SELECT *\n  FROM ks.tab WHERE id = 123e4567-e89b-12d3-a456-426614174000 AND b = 0xff"), "SELECT * FROM ks.tab WHERE id = ? AND b = ?");
+        assert_eq!(utility::normalise_sql("select $$it's a secret$$, $tag$O'Brien $$ nested$tag$ from t where id = $1"), "select '?', '?' from t where id = $1");
+        assert_eq!(utility::normalise_sql("select a$b$, $_x$unterminated"), "select a$b$, '?'");
+    }
+
     #[tokio::test]
     async fn integration_parse_statements_ysql() {
         let hostname = utility::get_hostname_ysql();
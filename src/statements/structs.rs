@@ -1,7 +1,8 @@
 //! The structs
 //!
 use chrono::{DateTime, Local};
-use std::collections::BTreeMap;
+use clap::ValueEnum;
+use std::collections::{BTreeMap, BTreeSet};
 /// The root struct for deserializing `/statements`.
 ///
 /// This struct contains a single json object that holds a list:
@@ -42,6 +43,7 @@ pub struct Statements {
 /// - queryid
 /// - userid
 /// - dbid
+///
 /// As you can see from the fields, we do not expose userid and dbid at this time.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Statement {
@@ -75,6 +77,7 @@ pub struct StatementsDiff {
 /// The struct that holds the first and second snapshot statistics.
 #[derive(Debug, Default)]
 pub struct GroupedStatements {
+    pub query_id: Option<i64>,
     pub first_snapshot_time: DateTime<Local>,
     pub first_calls: i64,
     pub first_total_time: f64,
@@ -83,4 +86,39 @@ pub struct GroupedStatements {
     pub second_calls: i64,
     pub second_total_time: f64,
     pub second_rows: i64,
-}
\ No newline at end of file
+}
+/// The struct that holds the statistics of a statement summed for all YSQL servers.
+///
+/// This is used for the cluster-wide report, where statements are grouped by query_id,
+/// or by the normalised query text if query_id is not available.
+#[derive(Debug, Default)]
+pub struct ClusterStatement {
+    pub query: String,
+    pub servers: BTreeSet<String>,
+    pub calls: i64,
+    pub total_time: f64,
+    pub rows: i64,
+    /// The highest number of seconds between the first and the second snapshot.
+    pub seconds: f64,
+}
+/// The order of the cluster-wide statements report (`--statements-sort`).
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[value(rename_all = "snake_case")]
+pub enum ClusterStatementsSort {
+    Calls,
+    TotalTime,
+    AvgTime,
+    Rows,
+}
+/// The order of the per server statements report (`--print-statements-sort`).
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[value(rename_all = "snake_case")]
+pub enum StatementsSort {
+    Calls,
+    TotalTime,
+    AvgTime,
+    MinTime,
+    MaxTime,
+    StddevTime,
+    Rows,
+}
@@ -1,14 +1,14 @@
 //! The impls and functions
 //!
-use chrono::Local;
-use std::{sync::mpsc::channel, time::Instant};
+use chrono::{DateTime, Local};
+use std::{collections::BTreeMap, sync::{mpsc::channel, LazyLock}, time::Instant};
 use regex::Regex;
 use scraper::{Html, Selector};
 use log::*;
 use anyhow::Result;
 use crate::utility;
 use crate::snapshot;
-use crate::threads::{Threads, AllThreads};
+use crate::threads::{Threads, AllThreads, ThreadsDiff, ThreadsDiffFields, ThreadsCpu, BTreeThreadsCpu, ThreadStack};
 use crate::Opts;
 
 impl AllThreads {
@@ -212,6 +212,264 @@ impl AllThreads {
         }
         Ok(())
     }
+    /// Group the threads with an identical stack over all threads and hosts, ordered by the number of threads descending.
+    pub fn group_stacks(
+        &self,
+        hostname_filter: &Regex,
+    ) -> Vec<ThreadStack>
+    {
+        let mut stacks: BTreeMap<String, ThreadStack> = BTreeMap::new();
+        for row in self.threads.iter().filter(|row| hostname_filter.is_match(&row.hostname_port))
+        {
+            let threadstack = stacks.entry(row.stack.clone()).or_insert_with(|| ThreadStack { stack: row.stack.clone(), ..Default::default() });
+            threadstack.count += 1;
+            threadstack.hostname_ports.insert(row.hostname_port.clone());
+            threadstack.thread_pools.insert(thread_pool_prefix(&row.thread_name));
+        }
+        let mut stacks: Vec<ThreadStack> = stacks.into_values().collect();
+        stacks.sort_by_key(|threadstack| std::cmp::Reverse(threadstack.count));
+        stacks
+    }
+    /// Print the threads grouped by identical stack, with the stack frames printed one per line.
+    pub fn print_stacks(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        for threadstack in self.group_stacks(hostname_filter)
+        {
+            println!("--------------------------------------------------------------------------------------------------------------------------------------");
+            println!("Threads: {}, hosts: {}, thread pools: {}",
+                     threadstack.count,
+                     threadstack.hostname_ports.len(),
+                     threadstack.thread_pools.iter().cloned().collect::<Vec<String>>().join(", "),
+            );
+            println!("--------------------------------------------------------------------------------------------------------------------------------------");
+            for frame in threadstack.stack.split(';').filter(|frame| !frame.is_empty())
+            {
+                println!("  {}", frame);
+            }
+        }
+    }
+    /// Add the stacks of the threads to a map of folded stacks and their counts.
+    /// The thread pool prefix is added as the outermost frame, so the flamegraph shows a tower per thread pool.
+    pub fn fold_stacks(
+        &self,
+        hostname_filter: &Regex,
+        folded_stacks: &mut BTreeMap<String, usize>,
+    )
+    {
+        for row in self.threads.iter().filter(|row| hostname_filter.is_match(&row.hostname_port))
+        {
+            let folded_stack = if row.stack.is_empty()
+            {
+                thread_pool_prefix(&row.thread_name)
+            }
+            else
+            {
+                format!("{};{}", thread_pool_prefix(&row.thread_name), row.stack.replace('\n', ""))
+            };
+            *folded_stacks.entry(folded_stack).or_default() += 1;
+        }
+    }
+}
+
+/// Parse the cumulative CPU seconds shown by /threadz, such as "2.960s", into a number.
+fn parse_cpu_seconds(cpu_seconds: &str) -> f64
+{
+    cpu_seconds.trim().trim_end_matches('s').parse().unwrap_or_default()
+}
+
+/// Return the thread pool prefix of a thread name, by removing the thread id and the number of the thread in the pool.
+///
+/// For example: `Master_reactorx-6127` is `Master_reactorx`, and `iotp_Master_3-6150` is `iotp_Master`.
+pub fn thread_pool_prefix(thread_name: &str) -> String
+{
+    static POOL_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(.*?)([-_:]?\d+)*$").unwrap());
+    POOL_PREFIX.captures(thread_name)
+        .map(|captures| captures[1].to_string())
+        .filter(|prefix| !prefix.is_empty())
+        .unwrap_or_else(|| thread_name.to_string())
+}
+
+impl ThreadsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+        begin_snapshot_time: &DateTime<Local>,
+    ) -> Result<ThreadsDiff>
+    {
+        let mut threadsdiff = ThreadsDiff::new();
+
+        let mut allthreads = AllThreads::new();
+        allthreads.threads = snapshot::read_snapshot_json(begin_snapshot, "threads")?;
+        threadsdiff.first_snapshot(allthreads);
+
+        let mut allthreads = AllThreads::new();
+        allthreads.threads = snapshot::read_snapshot_json(end_snapshot, "threads")?;
+        threadsdiff.second_snapshot(allthreads, begin_snapshot_time);
+
+        Ok(threadsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allthreads: AllThreads,
+    )
+    {
+        for row in allthreads.threads
+        {
+            self.btreethreadsdiff
+                .entry((row.hostname_port.clone(), row.thread_name.clone()))
+                .and_modify(|_| error!("Duplicate hostname:port + thread name entry: {}, {}", row.hostname_port, row.thread_name))
+                .or_insert(ThreadsDiffFields {
+                    first_snapshot_time: row.timestamp,
+                    first_user_cpu_s: parse_cpu_seconds(&row.cumulative_user_cpu_s),
+                    first_kernel_cpu_s: parse_cpu_seconds(&row.cumulative_kernel_cpu_s),
+                    first_iowait_cpu_s: parse_cpu_seconds(&row.cumulative_iowait_cpu_s),
+                    ..Default::default()
+                });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allthreads: AllThreads,
+        first_snapshot_time: &DateTime<Local>,
+    )
+    {
+        for row in allthreads.threads
+        {
+            self.btreethreadsdiff
+                .entry((row.hostname_port.clone(), row.thread_name.clone()))
+                .and_modify(|threadsdifffields| {
+                    threadsdifffields.second_snapshot_time = row.timestamp;
+                    threadsdifffields.second_user_cpu_s = parse_cpu_seconds(&row.cumulative_user_cpu_s);
+                    threadsdifffields.second_kernel_cpu_s = parse_cpu_seconds(&row.cumulative_kernel_cpu_s);
+                    threadsdifffields.second_iowait_cpu_s = parse_cpu_seconds(&row.cumulative_iowait_cpu_s);
+                })
+                // a thread that was started after the first snapshot used all its CPU time between the snapshots.
+                .or_insert(ThreadsDiffFields {
+                    first_snapshot_time: *first_snapshot_time,
+                    second_snapshot_time: row.timestamp,
+                    second_user_cpu_s: parse_cpu_seconds(&row.cumulative_user_cpu_s),
+                    second_kernel_cpu_s: parse_cpu_seconds(&row.cumulative_kernel_cpu_s),
+                    second_iowait_cpu_s: parse_cpu_seconds(&row.cumulative_iowait_cpu_s),
+                    ..Default::default()
+                });
+        }
+    }
+    /// Calculate the CPU seconds per thread, and per thread pool prefix.
+    /// Threads that were stopped before the second snapshot are skipped.
+    ///
+    /// The keys are (hostname_port, thread_name) and (hostname_port, thread pool prefix).
+    fn cpu_per_thread_and_pool(
+        &self,
+        hostname_filter: &Regex,
+    ) -> (BTreeThreadsCpu, BTreeThreadsCpu)
+    {
+        let mut threads = BTreeThreadsCpu::new();
+        let mut pools = BTreeThreadsCpu::new();
+        for ((hostname_port, thread_name), row) in self.btreethreadsdiff
+            .iter()
+            .filter(|((hostname_port, _), row)| hostname_filter.is_match(hostname_port)
+                && row.second_snapshot_time != DateTime::<Local>::default())
+        {
+            let threadscpu = ThreadsCpu {
+                threads: 1,
+                user_cpu_s: (row.second_user_cpu_s - row.first_user_cpu_s).max(0.0),
+                kernel_cpu_s: (row.second_kernel_cpu_s - row.first_kernel_cpu_s).max(0.0),
+                iowait_cpu_s: (row.second_iowait_cpu_s - row.first_iowait_cpu_s).max(0.0),
+                seconds: (row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 / 1000_f64,
+            };
+            let pool = pools.entry((hostname_port.clone(), thread_pool_prefix(thread_name))).or_default();
+            pool.threads += 1;
+            pool.user_cpu_s += threadscpu.user_cpu_s;
+            pool.kernel_cpu_s += threadscpu.kernel_cpu_s;
+            pool.iowait_cpu_s += threadscpu.iowait_cpu_s;
+            pool.seconds = pool.seconds.max(threadscpu.seconds);
+            threads.insert((hostname_port.clone(), thread_name.clone()), threadscpu);
+        }
+        (threads, pools)
+    }
+    /// Print the CPU seconds and the percentage of a CPU used between the snapshots per thread pool,
+    /// and per thread if details_enable is set, ordered by the CPU seconds used.
+    /// The CPU seconds are the user and kernel seconds, the io-wait seconds are shown separately.
+    /// Threads and pools that did not use CPU are not shown.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let (threads, pools) = self.cpu_per_thread_and_pool(hostname_filter);
+        let print_cpu = |heading: &str, cpu: BTreeThreadsCpu| {
+            let mut cpu: Vec<_> = cpu.into_iter()
+                .filter(|(_, threadscpu)| threadscpu.user_cpu_s + threadscpu.kernel_cpu_s + threadscpu.iowait_cpu_s > 0.0)
+                .collect();
+            cpu.sort_by(|(_, a), (_, b)| (b.user_cpu_s + b.kernel_cpu_s).total_cmp(&(a.user_cpu_s + a.kernel_cpu_s)));
+            println!("{:20} {:40} {:>7} {:>10} {:>10} {:>10} {:>10} {:>8}",
+                     "hostname_port", heading, "threads", "cpu_s", "user_s", "kernel_s", "iowait_s", "%cpu");
+            for ((hostname_port, name), threadscpu) in cpu
+            {
+                let cpu_s = threadscpu.user_cpu_s + threadscpu.kernel_cpu_s;
+                println!("{:20} {:40} {:7} {:10.3} {:10.3} {:10.3} {:10.3} {:8.2}",
+                         hostname_port,
+                         name,
+                         threadscpu.threads,
+                         cpu_s,
+                         threadscpu.user_cpu_s,
+                         threadscpu.kernel_cpu_s,
+                         threadscpu.iowait_cpu_s,
+                         if threadscpu.seconds == 0.0 { 0.0 } else { cpu_s / threadscpu.seconds * 100_f64 },
+                );
+            }
+        };
+        print_cpu("thread_pool", pools);
+        if *details_enable
+        {
+            print_cpu("thread_name", threads);
+        }
+    }
+    pub async fn adhoc_read_first_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    )
+    {
+        let allthreads = AllThreads::read_threads(hosts, ports, parallel).await;
+        self.first_snapshot(allthreads);
+    }
+    pub async fn adhoc_read_second_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+        first_snapshot_time: &DateTime<Local>,
+    )
+    {
+        let allthreads = AllThreads::read_threads(hosts, ports, parallel).await;
+        self.second_snapshot(allthreads, first_snapshot_time);
+    }
+}
+
+pub async fn threads_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let threads_diff = ThreadsDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp)?;
+    threads_diff.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
 }
 
 pub async fn print_threads(
@@ -222,16 +480,50 @@ pub async fn print_threads(
 ) -> Result<()>
 {
     let hostname_filter = utility::set_regex(&options.hostname_match);
-    match options.print_threads.as_ref().unwrap() {
+    let allthreads = match options.print_threads.as_ref().unwrap() {
         Some(snapshot_number) => {
             let mut allthreads = AllThreads::new();
             allthreads.threads = snapshot::read_snapshot_json(snapshot_number, "threads")?;
-            allthreads.print(&hostname_filter)?;
-        },
-        None => {
-            let allthreads = AllThreads::read_threads(&hosts, &ports, parallel).await;
-            allthreads.print(&hostname_filter)?;
+            allthreads
         },
+        None => AllThreads::read_threads(&hosts, &ports, parallel).await,
+    };
+    if options.threads_stacks
+    {
+        allthreads.print_stacks(&hostname_filter);
+    }
+    else
+    {
+        allthreads.print(&hostname_filter)?;
+    }
+    Ok(())
+}
+
+/// Print the stacks of the threads of one or more snapshots, or of the current threads, in folded stack format:
+/// `frame;frame;frame count`, which can be used as input for flamegraph tools.
+pub async fn print_threads_folded(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let mut folded_stacks: BTreeMap<String, usize> = BTreeMap::new();
+    let snapshot_numbers = options.threads_folded.as_ref().unwrap();
+    if snapshot_numbers.is_empty()
+    {
+        AllThreads::read_threads(&hosts, &ports, parallel).await.fold_stacks(&hostname_filter, &mut folded_stacks);
+    }
+    for snapshot_number in snapshot_numbers
+    {
+        let mut allthreads = AllThreads::new();
+        allthreads.threads = snapshot::read_snapshot_json(snapshot_number, "threads")?;
+        allthreads.fold_stacks(&hostname_filter, &mut folded_stacks);
+    }
+    for (folded_stack, count) in folded_stacks
+    {
+        println!("{} {}", folded_stack, count);
     }
     Ok(())
 }
@@ -240,6 +532,73 @@ pub async fn print_threads(
 mod tests {
     use super::*;
 
+    #[test]
+    fn unit_thread_pool_prefix() {
+        assert_eq!(thread_pool_prefix("Master_reactorx-6127"), "Master_reactorx");
+        assert_eq!(thread_pool_prefix("iotp_Master_3-6150"), "iotp_Master");
+        assert_eq!(thread_pool_prefix("rpc_tp_TabletServer-high-pri_0-7001"), "rpc_tp_TabletServer-high-pri");
+        assert_eq!(thread_pool_prefix("12345"), "12345");
+    }
+
+    #[test]
+    fn unit_threads_diff_cpu_per_thread_and_pool() {
+        let thread = |thread_name: &str, timestamp: DateTime<Local>, user: &str, kernel: &str| Threads {
+            hostname_port: "yb-1.local:9000".to_string(),
+            timestamp,
+            thread_name: thread_name.to_string(),
+            cumulative_user_cpu_s: user.to_string(),
+            cumulative_kernel_cpu_s: kernel.to_string(),
+            cumulative_iowait_cpu_s: "0.000s".to_string(),
+            ..Default::default()
+        };
+        let first_snapshot_time = Local::now();
+        let second_snapshot_time = first_snapshot_time + chrono::Duration::seconds(10);
+        let mut threadsdiff = ThreadsDiff::new();
+        threadsdiff.first_snapshot(AllThreads { threads: vec![
+            thread("Master_reactorx-6127", first_snapshot_time, "2.000s", "1.000s"),
+            thread("Master_reactorx-6128", first_snapshot_time, "1.000s", "0.000s"),
+            thread("stopped-6129", first_snapshot_time, "1.000s", "0.000s"),
+        ]});
+        threadsdiff.second_snapshot(AllThreads { threads: vec![
+            thread("Master_reactorx-6127", second_snapshot_time, "4.000s", "2.000s"),
+            thread("Master_reactorx-6128", second_snapshot_time, "2.000s", "0.000s"),
+            thread("new-6130", second_snapshot_time, "0.500s", "0.000s"),
+        ]}, &first_snapshot_time);
+        let (threads, pools) = threadsdiff.cpu_per_thread_and_pool(&utility::set_regex(&None));
+        assert_eq!(threads.len(), 3);
+        assert_eq!(threads[&("yb-1.local:9000".to_string(), "new-6130".to_string())].user_cpu_s, 0.5);
+        assert_eq!(pools[&("yb-1.local:9000".to_string(), "Master_reactorx".to_string())],
+                   ThreadsCpu { threads: 2, user_cpu_s: 3.0, kernel_cpu_s: 1.0, iowait_cpu_s: 0.0, seconds: 10.0 });
+    }
+
+    #[test]
+    fn unit_threads_group_and_fold_stacks() {
+        let thread = |hostname_port: &str, thread_name: &str, stack: &str| Threads {
+            hostname_port: hostname_port.to_string(),
+            thread_name: thread_name.to_string(),
+            stack: stack.to_string(),
+            ..Default::default()
+        };
+        let allthreads = AllThreads { threads: vec![
+            thread("yb-1.local:9000", "rpc_tp_TabletServer_0-7001", "__clone;start_thread;yb::Thread::SuperviseThread();pthread_cond_wait"),
+            thread("yb-1.local:9000", "rpc_tp_TabletServer_1-7002", "__clone;start_thread;yb::Thread::SuperviseThread();pthread_cond_wait"),
+            thread("yb-2.local:9000", "rpc_tp_TabletServer_0-7101", "__clone;start_thread;yb::Thread::SuperviseThread();pthread_cond_wait"),
+            thread("yb-2.local:9000", "Master_reactorx-6127", "__clone;start_thread;ev_run;epoll_poll"),
+        ]};
+        let stacks = allthreads.group_stacks(&utility::set_regex(&None));
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(stacks[0].count, 3);
+        assert_eq!(stacks[0].hostname_ports.len(), 2);
+        assert_eq!(stacks[0].thread_pools.iter().collect::<Vec<_>>(), vec!["rpc_tp_TabletServer"]);
+        assert_eq!(stacks[1].count, 1);
+
+        let mut folded_stacks = BTreeMap::new();
+        allthreads.fold_stacks(&utility::set_regex(&Some("yb-1".to_string())), &mut folded_stacks);
+        allthreads.fold_stacks(&utility::set_regex(&Some("yb-1".to_string())), &mut folded_stacks);
+        assert_eq!(folded_stacks.len(), 1);
+        assert_eq!(folded_stacks["rpc_tp_TabletServer;__clone;start_thread;yb::Thread::SuperviseThread();pthread_cond_wait"], 4);
+    }
+
     #[test]
     fn unit_parse_threads_data() {
         // This is what /threadz?group=all returns.
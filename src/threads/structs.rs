@@ -1,6 +1,7 @@
 //! The structs
 //!
 use chrono::{DateTime, Local};
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Threads {
@@ -16,4 +17,47 @@ pub struct Threads {
 #[derive(Debug, Default)]
 pub struct AllThreads {
     pub threads: Vec<Threads>,
-}
\ No newline at end of file
+}
+/// A stack shared by one or more threads, on one or more hosts.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ThreadStack {
+    /// The number of threads with this stack.
+    pub count: usize,
+    pub hostname_ports: BTreeSet<String>,
+    /// The thread pool prefixes of the threads with this stack.
+    pub thread_pools: BTreeSet<String>,
+    /// The stack frames, outermost first, separated by ";".
+    pub stack: String,
+}
+// diff
+/// BTreeMap for storing a threads diff struct per `hostname_port` and `thread_name`.
+type BTreeThreadsDiff = BTreeMap<(String, String), ThreadsDiffFields>;
+/// The wrapper struct for holding the btreemap holding the diff structs.
+#[derive(Debug, Default)]
+pub struct ThreadsDiff {
+    pub btreethreadsdiff: BTreeThreadsDiff,
+}
+/// The struct that holds the first and second snapshot cumulative CPU seconds of a thread.
+#[derive(Debug, Default)]
+pub struct ThreadsDiffFields {
+    pub first_snapshot_time: DateTime<Local>,
+    pub first_user_cpu_s: f64,
+    pub first_kernel_cpu_s: f64,
+    pub first_iowait_cpu_s: f64,
+    pub second_snapshot_time: DateTime<Local>,
+    pub second_user_cpu_s: f64,
+    pub second_kernel_cpu_s: f64,
+    pub second_iowait_cpu_s: f64,
+}
+/// BTreeMap for storing the CPU seconds used per `hostname_port` and thread name or thread pool prefix.
+pub type BTreeThreadsCpu = BTreeMap<(String, String), ThreadsCpu>;
+/// The CPU seconds used between the snapshots by a thread or a thread pool.
+#[derive(Debug, Default, PartialEq)]
+pub struct ThreadsCpu {
+    pub threads: usize,
+    pub user_cpu_s: f64,
+    pub kernel_cpu_s: f64,
+    pub iowait_cpu_s: f64,
+    /// The number of seconds between the first and the second snapshot.
+    pub seconds: f64,
+}
@@ -1,7 +1,8 @@
 //! Utilities
 //use port_scanner::scan_port_addr;
 use log::*;
-use std::{collections::HashMap, env, fs, io::Write, time::Duration};
+use std::{collections::{BTreeSet, HashMap}, env, fs, io::Write, sync::LazyLock, time::Duration};
+use colored::*;
 use anyhow::{Context, Result};
 use regex::Regex;
 //use qscan::{QScanner, QScanResult, QScanType, QscanTcpConnectState, QScanTcpConnectState};
@@ -86,6 +87,68 @@ pub fn http_get(
     }
 }
 
+/// Reads the http endpoint as specified by the caller, and returns the result as bytes.
+/// This is used for endpoints that return binary data, and can take longer than the default request timeout,
+/// such as a CPU profile.
+pub fn http_get_bytes(
+    host: &str,
+    port: &str,
+    url: &str,
+    timeout: Duration,
+) -> Vec<u8>
+{
+    if let Ok(data_from_web_request) = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_millis(200))
+        .timeout(timeout)
+        .danger_accept_invalid_certs(ACCEPT_INVALID_CERTS)
+        .build()
+        .unwrap()
+        .get(format!("http://{}:{}/{}", host, port, url))
+        .send()
+    {
+        if ! &data_from_web_request.status().is_success()
+        {
+            debug!("Non success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+            return Vec::new();
+        }
+        debug!("Success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+        data_from_web_request.bytes().map(|bytes| bytes.to_vec()).unwrap_or_default()
+    } else {
+        debug!("Non-Ok success response: {}:{}/{}", host, port, url);
+        Vec::new()
+    }
+}
+
+/// Posts the body to the http endpoint as specified by the caller, and returns the result as String.
+pub fn http_post(
+    host: &str,
+    port: &str,
+    url: &str,
+    body: String,
+) -> String
+{
+    if let Ok(data_from_web_request) = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_millis(200))
+        .danger_accept_invalid_certs(ACCEPT_INVALID_CERTS)
+        .build()
+        .unwrap()
+        .post(format!("http://{}:{}/{}", host, port, url))
+        .body(body)
+        .send()
+    {
+        if ! &data_from_web_request.status().is_success()
+        {
+            debug!("Non success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+            return String::new();
+        }
+        debug!("Success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+        data_from_web_request.text().unwrap_or_default()
+    } else {
+        debug!("Non-Ok success response: {}:{}/{}", host, port, url);
+        String::new()
+    }
+}
+
 /// Take the hosts from the [Option] struct, and:
 /// - adds it to the changed_options hashmap if necessary.
 /// - returns a Vec<&str>.
@@ -193,6 +256,39 @@ pub fn set_parallel(
     parallel
 }
 
+/// Take the prometheus targets from the [Option] struct, and:
+/// - adds it to the changed_options hashmap if necessary.
+/// - returns a Vec<&str> of `name=host:port/path` targets, which is empty if no targets are set.
+pub fn set_prometheus_targets(
+    option: &Option<String>,
+    changed_options: &mut HashMap<&str, String>,
+) -> Vec<&'static str>
+{
+    // is --prometheus-targets set?
+    let targets_string = if option.is_some() {
+        info!("prometheus-targets argument set: using: {}", &option.as_ref().unwrap());
+        // insert into changed_options to be written later on.
+        changed_options.insert("YBSTATS_PROMETHEUS_TARGETS", option.as_ref().unwrap().to_string());
+        option.clone().unwrap()
+    } else {
+        // is the environment variable YBSTATS_PROMETHEUS_TARGETS set (via dotenv().ok())?
+        match env::var("YBSTATS_PROMETHEUS_TARGETS") {
+            Ok(set_var) => {
+                info!("prometheus-targets not set: set via .env: YBSTATS_PROMETHEUS_TARGETS: {}", set_var);
+                changed_options.insert("YBSTATS_PROMETHEUS_TARGETS", set_var.to_owned());
+                set_var
+            }
+            Err(_e) => {
+                info!("prometheus-targets not set: and not set via .env: no prometheus targets");
+                String::new()
+            }
+        }
+    };
+    let static_targets: &'static str = Box::leak(targets_string.into_boxed_str());
+    let targets: Vec<&'static str> = static_targets.split(',').filter(|target| !target.is_empty()).collect();
+    targets
+}
+
 /// Simple helper routine to create a regex from an `&Option<String>`.
 pub fn set_regex(
     regex: &Option<String>,
@@ -204,6 +300,190 @@ pub fn set_regex(
     }
 }
 
+/// Calculate the difference of a counter between a first and a second snapshot.
+///
+/// A counter that is lower in the second snapshot has been reset, which is what happens when a server restarts.
+/// For a counter that has been reset, or a counter of a server that is known to have been restarted,
+/// the second value is the amount since the reset, and is returned as the difference.
+/// The returned boolean is true if the counter has been reset.
+pub fn counter_difference<T>(
+    first_value: T,
+    second_value: T,
+    restarted: bool,
+) -> (T, bool)
+where
+    T: PartialOrd + std::ops::Sub<Output = T> + Copy,
+{
+    if restarted || second_value < first_value {
+        (second_value, true)
+    } else {
+        (second_value - first_value, false)
+    }
+}
+
+/// Normalise SQL text by replacing the literals with placeholders.
+///
+/// This removes data from the SQL text, and makes SQL texts that only differ in literals identical:
+/// - string literals ('text', with '' as escaped quote) are replaced by '?'.
+/// - escape string literals (E'text', with \' and '' as escaped quote) are replaced by '?'.
+/// - a string literal that is not closed is replaced by '?' until the end of the text.
+/// - dollar-quoted string literals ($$text$$, $tag$text$tag$) are replaced by '?'.
+/// - numeric literals (1, 1.5, 1e10, 0xff) are replaced by ?.
+/// - (CQL) uuid literals are replaced by ?.
+/// - whitespace is collapsed into a single space.
+///
+/// Identifiers, including quoted identifiers ("name"), and YSQL placeholders ($1) are left untouched.
+pub fn normalise_sql(
+    sql: &str,
+) -> String
+{
+    static UUID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b").unwrap());
+    let sql = UUID.replace_all(sql, "?");
+    let characters: Vec<char> = sql.chars().collect();
+    let mut normalised = String::with_capacity(characters.len());
+    let mut position = 0;
+    while position < characters.len()
+    {
+        let character = characters[position];
+        // the character before the current one, to see if a number is part of an identifier or placeholder.
+        let previous = normalised.chars().last().unwrap_or(' ');
+        if character == '\''
+            || (matches!(character, 'e' | 'E')
+            && characters.get(position + 1) == Some(&'\'')
+            && !(previous.is_alphanumeric() || previous == '_' || previous == '$'))
+        {
+            // string literal: skip until the closing quote, where two quotes are an escaped quote.
+            // in an escape string literal (E'text') a backslash escapes the next character, including a quote.
+            // a literal that is not closed is skipped until the end of the text.
+            let escape_string = character != '\'';
+            if escape_string { position += 1 };
+            position += 1;
+            while position < characters.len()
+            {
+                if escape_string && characters[position] == '\\'
+                {
+                    position += 2;
+                    continue;
+                }
+                if characters[position] == '\''
+                {
+                    if position + 1 < characters.len() && characters[position + 1] == '\''
+                    {
+                        position += 2;
+                        continue;
+                    }
+                    break;
+                }
+                position += 1;
+            }
+            normalised.push_str("'?'");
+            position += 1;
+        }
+        else if let Some(tag) = dollar_quote_tag(&characters[position..], previous)
+        {
+            // dollar-quoted string literal: skip until the closing tag.
+            position += tag.len();
+            while position < characters.len() && !characters[position..].starts_with(&tag)
+            {
+                position += 1;
+            }
+            normalised.push_str("'?'");
+            position += tag.len();
+        }
+        else if character == '"'
+        {
+            // quoted identifier: copy until the closing quote.
+            normalised.push(character);
+            position += 1;
+            while position < characters.len() && characters[position] != '"'
+            {
+                normalised.push(characters[position]);
+                position += 1;
+            }
+            if position < characters.len()
+            {
+                normalised.push('"');
+            }
+            position += 1;
+        }
+        else if character.is_ascii_digit()
+            && !(previous.is_alphanumeric() || previous == '_' || previous == '$')
+        {
+            // numeric literal, including hexadecimal, decimals and exponents.
+            position += 1;
+            while position < characters.len()
+                && (characters[position].is_ascii_alphanumeric()
+                || characters[position] == '.'
+                || ((characters[position] == '-' || characters[position] == '+') && matches!(characters[position - 1], 'e' | 'E')))
+            {
+                position += 1;
+            }
+            normalised.push('?');
+        }
+        else if character.is_whitespace()
+        {
+            while position < characters.len() && characters[position].is_whitespace()
+            {
+                position += 1;
+            }
+            if !normalised.is_empty() && position < characters.len()
+            {
+                normalised.push(' ');
+            }
+        }
+        else
+        {
+            normalised.push(character);
+            position += 1;
+        }
+    }
+    normalised
+}
+
+/// Return the opening tag ($$ or $tag$) if the characters start with a dollar-quoted string literal.
+///
+/// A tag can not start with a digit, so a YSQL placeholder ($1) is not a tag,
+/// and a dollar sign that is part of an identifier (a$b) does not start a tag either.
+fn dollar_quote_tag(
+    characters: &[char],
+    previous: char,
+) -> Option<Vec<char>>
+{
+    if characters.first() != Some(&'$') || previous.is_alphanumeric() || previous == '_' || previous == '$'
+    {
+        return None;
+    }
+    let mut length = 1;
+    while length < characters.len() && (characters[length].is_alphanumeric() || characters[length] == '_')
+    {
+        if length == 1 && characters[length].is_ascii_digit()
+        {
+            return None;
+        }
+        length += 1;
+    }
+    if characters.get(length) == Some(&'$')
+    {
+        Some(characters[..=length].to_vec())
+    }
+    else
+    {
+        None
+    }
+}
+
+/// Print the servers that have been restarted between two snapshots.
+pub fn print_restarts(
+    restarted_servers: &BTreeSet<String>,
+    hostname_filter: &Regex,
+)
+{
+    for hostname_port in restarted_servers.iter().filter(|hostname_port| hostname_filter.is_match(hostname_port))
+    {
+        println!("{} {:20} restarted between the snapshots, counters are shown since the restart (reset)", "!".red(), hostname_port);
+    }
+}
+
 /// If writing the '.env' file is allowed via write_dotenv,
 /// take the changed_options hashmap, and write it.
 pub fn dotenv_writer(